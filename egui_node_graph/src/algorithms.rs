@@ -0,0 +1,245 @@
+use super::*;
+use std::collections::{HashSet, VecDeque};
+
+/// A cycle found in a [`Graph`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    /// The nodes in the cycle, in connection order: Each node feeds the next
+    /// one, and the last node feeds the first.
+    pub nodes: Vec<NodeId>,
+    /// The connections forming the cycle, as `(input, output)` pairs. The
+    /// connection at index `i` goes from `nodes[i]` to the next node.
+    pub connections: Vec<(InputId, OutputId)>,
+}
+
+/// An outgoing edge of a node: The node it connects to and the connection.
+type Edge = (NodeId, InputId, OutputId);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    InProgress,
+    Done,
+}
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
+    /// Builds the list of outgoing edges for every node in the graph, from
+    /// the index of outgoing connections.
+    fn outgoing_edges(&self) -> SecondaryMap<NodeId, Vec<Edge>> {
+        self.iter_nodes()
+            .map(|node| {
+                let edges = self[node]
+                    .output_ids()
+                    .flat_map(|output| {
+                        self.outgoing(output)
+                            .iter()
+                            .map(move |input| (self[*input].node, *input, output))
+                    })
+                    .collect();
+                (node, edges)
+            })
+            .collect()
+    }
+
+    /// Returns all the nodes of the graph, sorted so that every node comes
+    /// after all the nodes connected to its inputs. This is the order in which
+    /// nodes need to be evaluated.
+    ///
+    /// Returns [`EguiGraphError::CycleDetected`] if the graph contains a
+    /// cycle, since no such order exists in that case.
+    pub fn topological_order(&self) -> Result<Vec<NodeId>, EguiGraphError> {
+        let edges = self.outgoing_edges();
+
+        let mut in_degree: SecondaryMap<NodeId, usize> =
+            self.iter_nodes().map(|node| (node, 0)).collect();
+        for (to, _, _) in edges.values().flatten() {
+            in_degree[*to] += 1;
+        }
+
+        let mut queue: VecDeque<NodeId> = self
+            .iter_nodes()
+            .filter(|node| in_degree[*node] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for (to, _, _) in &edges[node] {
+                in_degree[*to] -= 1;
+                if in_degree[*to] == 0 {
+                    queue.push_back(*to);
+                }
+            }
+        }
+
+        if order.len() == self.nodes.len() {
+            Ok(order)
+        } else {
+            let cycle = self
+                .find_cycle()
+                .expect("Nodes left unsorted should be part of a cycle");
+            Err(EguiGraphError::CycleDetected(cycle))
+        }
+    }
+
+    /// Finds a cycle in the graph, if there is any. When there are several
+    /// cycles, only one of them is returned.
+    pub fn find_cycle(&self) -> Option<Cycle> {
+        let edges = self.outgoing_edges();
+        let mut visits = SecondaryMap::<NodeId, Visit>::new();
+
+        for root in self.iter_nodes() {
+            if visits.contains_key(root) {
+                continue;
+            }
+
+            // Iterative depth-first search. Each stack element stores the node
+            // and the index of the next edge to explore. The connection at
+            // `path[i]` goes from `stack[i]` to `stack[i + 1]`.
+            let mut stack = vec![(root, 0)];
+            let mut path: Vec<(InputId, OutputId)> = vec![];
+            visits.insert(root, Visit::InProgress);
+
+            while let Some(&(node, next_edge)) = stack.last() {
+                if let Some(&(to, input, output)) = edges[node].get(next_edge) {
+                    if let Some(top) = stack.last_mut() {
+                        top.1 += 1;
+                    }
+                    match visits.get(to) {
+                        None => {
+                            visits.insert(to, Visit::InProgress);
+                            stack.push((to, 0));
+                            path.push((input, output));
+                        }
+                        Some(Visit::InProgress) => {
+                            // Found an edge back into the current path.
+                            let start = stack
+                                .iter()
+                                .position(|(n, _)| *n == to)
+                                .expect("In-progress nodes should be in the stack");
+                            let mut connections = path[start..].to_vec();
+                            connections.push((input, output));
+                            return Some(Cycle {
+                                nodes: stack[start..].iter().map(|(n, _)| *n).collect(),
+                                connections,
+                            });
+                        }
+                        Some(Visit::Done) => {}
+                    }
+                } else {
+                    visits.insert(node, Visit::Done);
+                    stack.pop();
+                    path.pop();
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns the nodes that can't be connected to `param` without
+    /// introducing a cycle. For an output parameter, these are the nodes whose
+    /// inputs can't receive a connection from it. For an input parameter, the
+    /// nodes whose outputs can't be connected to it. The node containing
+    /// `param` is always part of the result.
+    pub fn cycle_forming_nodes(&self, param: AnyParameterId) -> HashSet<NodeId> {
+        let (start, direction) = match param {
+            // A node receiving a connection from this output would close a
+            // cycle if it is upstream from the output's node.
+            AnyParameterId::Output(output) => (self[output].node, TraversalDirection::Upstream),
+            // Conversely, a node feeding this input would close a cycle if it
+            // is downstream from the input's node.
+            AnyParameterId::Input(input) => (self[input].node, TraversalDirection::Downstream),
+        };
+
+        std::iter::once(start)
+            .chain(self.traverse(start, direction, TraversalOrder::BreadthFirst))
+            .collect()
+    }
+
+    /// Returns whether connecting `output` to `input` would introduce a cycle
+    /// in the graph. Connecting two parameters of the same node always does.
+    pub fn would_create_cycle(&self, output: OutputId, input: InputId) -> bool {
+        let from = self[output].node;
+        let to = self[input].node;
+
+        // The new connection closes a cycle iff the node producing the output
+        // is already reachable from the node receiving the input.
+        self.is_reachable(to, from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    pub fn test_topological_order() {
+        let mut graph = TestGraph::new();
+        let c = add_node(&mut graph, &["in0", "in1"]);
+        let b = add_node(&mut graph, &["in0"]);
+        let a = add_node(&mut graph, &[]);
+        connect(&mut graph, a, b, "in0");
+        connect(&mut graph, b, c, "in0");
+        connect(&mut graph, a, c, "in1");
+
+        assert_eq!(graph.topological_order().unwrap(), vec![a, b, c]);
+        assert_eq!(graph.find_cycle(), None);
+    }
+
+    #[test]
+    pub fn test_cycles() {
+        let mut graph = TestGraph::new();
+        let a = add_node(&mut graph, &["in0"]);
+        let b = add_node(&mut graph, &["in0"]);
+        let c = add_node(&mut graph, &["in0"]);
+        connect(&mut graph, a, b, "in0");
+        connect(&mut graph, b, c, "in0");
+
+        let a_out = graph[a].output_ids().next().unwrap();
+        let a_in = graph[a].input_ids().next().unwrap();
+        let c_out = graph[c].output_ids().next().unwrap();
+        assert!(graph.would_create_cycle(c_out, a_in));
+        assert!(graph.would_create_cycle(a_out, a_in));
+
+        connect(&mut graph, c, a, "in0");
+        let cycle = graph.find_cycle().unwrap();
+        assert_eq!(cycle.nodes.len(), 3);
+        assert_eq!(cycle.connections.len(), 3);
+        for (i, (input, output)) in cycle.connections.iter().enumerate() {
+            assert_eq!(graph[*output].node, cycle.nodes[i]);
+            assert_eq!(graph[*input].node, cycle.nodes[(i + 1) % 3]);
+        }
+        assert!(matches!(
+            graph.topological_order(),
+            Err(EguiGraphError::CycleDetected(_))
+        ));
+    }
+
+    #[test]
+    pub fn test_cycle_forming_nodes() {
+        let mut graph = TestGraph::new();
+        let a = add_node(&mut graph, &["in0"]);
+        let b = add_node(&mut graph, &["in0", "in1"]);
+        let c = add_node(&mut graph, &["in0"]);
+        let d = add_node(&mut graph, &[]);
+        connect(&mut graph, a, b, "in0");
+        connect(&mut graph, d, b, "in1");
+        connect(&mut graph, b, c, "in0");
+
+        let output =
+            |node: NodeId| AnyParameterId::Output(graph[node].output_ids().next().unwrap());
+        let input = |node: NodeId| AnyParameterId::Input(graph[node].input_ids().next().unwrap());
+        // An output can't feed the nodes upstream from it
+        assert_eq!(
+            graph.cycle_forming_nodes(output(c)),
+            HashSet::from([a, b, c, d])
+        );
+        assert_eq!(graph.cycle_forming_nodes(output(d)), HashSet::from([d]));
+        // An input can't be fed by the nodes downstream from it
+        assert_eq!(
+            graph.cycle_forming_nodes(input(a)),
+            HashSet::from([a, b, c])
+        );
+        assert_eq!(graph.cycle_forming_nodes(input(c)), HashSet::from([c]));
+    }
+}
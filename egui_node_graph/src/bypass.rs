@@ -0,0 +1,22 @@
+use super::*;
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
+    /// Whether `node_id` is bypassed. See [`Node::bypassed`].
+    pub fn is_bypassed(&self, node_id: NodeId) -> bool {
+        self[node_id].bypassed
+    }
+
+    /// Bypasses `node_id`, or enables it again. When the state changes, the
+    /// node is marked as dirty, together with the nodes downstream from it,
+    /// since their results change too.
+    ///
+    /// While bypassed, evaluation forwards the inputs of the node to its
+    /// outputs, as declared by [`NodeEvaluatorTrait::pass_through`].
+    pub fn set_bypassed(&mut self, node_id: NodeId, bypassed: bool) {
+        if self[node_id].bypassed != bypassed {
+            self.nodes[node_id].bypassed = bypassed;
+            self.mark_dirty(node_id);
+            self.notify(GraphEvent::BypassChanged(node_id));
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use super::*;
+use std::collections::HashSet;
+
+#[cfg(feature = "persistence")]
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Implemented for the types that can be copied to the system clipboard, that
+/// is, the types that can be serialized with serde. See
+/// [`GraphEditorState::handle_clipboard`].
+#[cfg(feature = "persistence")]
+pub trait ClipboardData: Serialize + DeserializeOwned {}
+#[cfg(feature = "persistence")]
+impl<T: Serialize + DeserializeOwned> ClipboardData for T {}
+
+/// A set of nodes copied from the editor. This is a standalone graph that can
+/// be pasted into any editor, and serialized to text with the `persistence`
+/// feature.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct ClipboardContents<NodeData, DataType, ValueType> {
+    /// The copied nodes, with the connections between them.
+    pub graph: Graph<NodeData, DataType, ValueType>,
+    /// The draw order of the copied nodes.
+    pub node_order: Vec<NodeId>,
+    /// The position of each node, relative to the top-left corner of the
+    /// copied nodes.
+    pub node_positions: SecondaryMap<NodeId, egui::Vec2>,
+    /// The knots of the copied wires, relative to the top-left corner of the
+    /// copied nodes.
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub knots: WireKnots,
+    /// The frames around the copied nodes, in draw order, relative to the
+    /// top-left corner of the copied nodes. See
+    /// [`GraphEditorState::copy_nodes`].
+    #[cfg_attr(
+        feature = "persistence",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub frames: Vec<CommentFrame>,
+}
+
+#[cfg(feature = "persistence")]
+impl<NodeData, DataType, ValueType> ClipboardContents<NodeData, DataType, ValueType>
+where
+    NodeData: ClipboardData,
+    DataType: ClipboardData,
+    ValueType: ClipboardData,
+{
+    /// Serializes the copied nodes to text, to store them in the clipboard.
+    pub fn to_text(&self) -> Result<String, EguiGraphError> {
+        ron::to_string(self).map_err(|err| EguiGraphError::Clipboard(err.to_string()))
+    }
+
+    /// Parses copied nodes from text produced by
+    /// [`ClipboardContents::to_text`].
+    pub fn from_text(text: &str) -> Result<Self, EguiGraphError> {
+        ron::from_str(text).map_err(|err| EguiGraphError::Clipboard(err.to_string()))
+    }
+}
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType>
+where
+    NodeData: Clone,
+    DataType: Clone,
+    ValueType: Clone,
+{
+    /// Copies `nodes` to a new graph, together with the connections between
+    /// them. Returns the new graph, and the ids of the copied nodes and
+    /// parameters in the new graph.
+    pub fn copy_nodes(&self, nodes: &[NodeId]) -> (Self, IdRemap) {
+        let copied: HashSet<NodeId> = nodes
+            .iter()
+            .copied()
+            .filter(|node| self.nodes.contains_key(*node))
+            .collect();
+
+        let mut graph = Graph::new();
+        let mut remap = IdRemap::default();
+        for node_id in nodes.iter().copied() {
+            if !copied.contains(&node_id) || remap.nodes.contains_key(node_id) {
+                continue;
+            }
+            let node = &self[node_id];
+            let new_id = graph.add_node(
+                node.label.clone(),
+                node.user_data.clone(),
+                |graph, new_id| {
+                    for (name, input) in &node.inputs {
+                        let param = self[*input].clone();
+                        let new_input = graph.add_input_param_like(new_id, name.clone(), param);
+                        remap.inputs.insert(*input, new_input);
+                    }
+                    for (name, output) in &node.outputs {
+                        let param = self[*output].clone();
+                        let new_output = graph.add_output_param_like(new_id, name.clone(), param);
+                        remap.outputs.insert(*output, new_output);
+                    }
+                },
+            );
+            graph.set_bypassed(new_id, node.bypassed);
+            remap.nodes.insert(node_id, new_id);
+
+            if let Some(group) = self.groups.get(node_id) {
+                let mut group = group.clone();
+                group.remap_ports(&remap.inputs, &remap.outputs);
+                graph.groups.insert(new_id, group);
+            }
+        }
+
+        for (input, output) in self.iter_connections() {
+            if let (Some(input), Some(output)) = (remap.input(input), remap.output(output)) {
+                graph.add_connection(output, input);
+            }
+        }
+        (graph, remap)
+    }
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+where
+    NodeData: Clone,
+    DataType: Clone,
+    ValueType: Clone,
+{
+    /// Copies `nodes` of the open graph, with the connections between them,
+    /// their positions and the knots of their wires. The frames containing
+    /// only copied nodes are copied too.
+    pub fn copy_nodes(
+        &mut self,
+        nodes: &[NodeId],
+    ) -> ClipboardContents<NodeData, DataType, ValueType> {
+        self.with_open_group(|state| state.copy_nodes_in_open_graph(nodes))
+    }
+
+    /// Pastes copied nodes into the open graph, with their top-left corner at
+    /// `position`. The pasted nodes become the selection. Returns the ids of
+    /// the pasted nodes.
+    pub fn paste(
+        &mut self,
+        contents: ClipboardContents<NodeData, DataType, ValueType>,
+        position: egui::Pos2,
+    ) -> Vec<NodeId> {
+        self.with_open_group(|state| state.paste_in_open_graph(contents, position))
+    }
+
+    /// Like [`GraphEditorState::copy_nodes`], for a state that has the open
+    /// group swapped in.
+    pub(crate) fn copy_nodes_in_open_graph(
+        &self,
+        nodes: &[NodeId],
+    ) -> ClipboardContents<NodeData, DataType, ValueType> {
+        let (graph, remap) = self.graph.copy_nodes(nodes);
+
+        let top_left = remap
+            .nodes
+            .keys()
+            .filter_map(|node| self.node_positions.get(node))
+            .fold(None, |min: Option<egui::Pos2>, pos| {
+                Some(min.map_or(*pos, |min| min.min(*pos)))
+            })
+            .unwrap_or(egui::Pos2::ZERO);
+
+        let mut knots = WireKnots::default();
+        for (output, input, wire_knots) in self.knots.iter() {
+            if let (Some(output), Some(input)) = (remap.output(output), remap.input(input)) {
+                let wire_knots = wire_knots.iter().map(|knot| *knot - top_left.to_vec2());
+                knots.set(output, input, wire_knots.collect());
+            }
+        }
+        let frames = self
+            .frame_order
+            .iter()
+            .filter(|frame| {
+                let framed = self.frame_nodes_in_open_graph(**frame);
+                !framed.is_empty() && framed.iter().all(|node| remap.nodes.contains_key(*node))
+            })
+            .map(|frame| {
+                let mut frame = self.frames[*frame].clone();
+                frame.position -= top_left.to_vec2();
+                frame
+            })
+            .collect();
+
+        ClipboardContents {
+            graph,
+            node_order: self
+                .node_order
+                .iter()
+                .filter_map(|node| remap.node(*node))
+                .collect(),
+            node_positions: remap
+                .nodes
+                .iter()
+                .map(|(node, new_id)| {
+                    let pos = self.node_positions.get(node).copied().unwrap_or(top_left);
+                    (*new_id, pos - top_left)
+                })
+                .collect(),
+            knots,
+            frames,
+        }
+    }
+
+    /// Like [`GraphEditorState::paste`], for a state that has the open group
+    /// swapped in.
+    pub(crate) fn paste_in_open_graph(
+        &mut self,
+        contents: ClipboardContents<NodeData, DataType, ValueType>,
+        position: egui::Pos2,
+    ) -> Vec<NodeId> {
+        let ClipboardContents {
+            graph,
+            node_order,
+            node_positions,
+            knots,
+            frames,
+        } = contents;
+        let node_positions = node_positions
+            .iter()
+            .map(|(node, pos)| (node, pos.to_pos2()))
+            .collect();
+        let remap = self.merge_in_open_graph(
+            graph,
+            &node_order,
+            &node_positions,
+            &knots,
+            frames,
+            position.to_vec2(),
+        );
+        let pasted: Vec<NodeId> = remap.nodes.values().copied().collect();
+        self.selected_nodes = pasted.clone();
+        pasted
+    }
+}
+
+#[cfg(feature = "persistence")]
+impl<NodeData, DataType, ValueType, NodeTemplate, UserResponse, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+where
+    NodeData: NodeDataTrait<
+            Response = UserResponse,
+            UserState = UserState,
+            DataType = DataType,
+            ValueType = ValueType,
+        > + Clone
+        + ClipboardData,
+    UserResponse: UserResponseTrait,
+    DataType: Clone + ClipboardData,
+    ValueType: Clone + ClipboardData,
+{
+    /// Copies, cuts and pastes the nodes of the open graph through the system
+    /// clipboard, when the cursor is over `ui` and no widget has the focus.
+    /// Call it before [`GraphEditorState::draw_graph_editor`] and pass it the
+    /// returned responses: the cut nodes are deleted by the editor, and the
+    /// pasted nodes are reported with [`NodeResponse::CreatedNode`].
+    #[must_use]
+    pub fn handle_clipboard(
+        &mut self,
+        ui: &egui::Ui,
+        user_state: &mut UserState,
+    ) -> Vec<NodeResponse<UserResponse, NodeData>> {
+        let mut responses = vec![];
+        let editor_rect = ui.max_rect();
+        if !ui.rect_contains_pointer(editor_rect) || ui.ctx().memory(|mem| mem.focus().is_some()) {
+            return responses;
+        }
+        let cursor_pos = ui
+            .ctx()
+            .input(|i| i.pointer.hover_pos().unwrap_or(egui::Pos2::ZERO));
+        let events = ui.ctx().input(|i| i.events.clone());
+        self.with_open_group(|state| {
+            for event in events {
+                match event {
+                    egui::Event::Copy | egui::Event::Cut if !state.selected_nodes.is_empty() => {
+                        let nodes = state.selected_nodes.clone();
+                        if let Ok(text) = state.copy_nodes_in_open_graph(&nodes).to_text() {
+                            ui.ctx().output_mut(|o| o.copied_text = text);
+                        }
+                        if event == egui::Event::Cut {
+                            for node in nodes {
+                                let can_delete = state.graph[node].user_data.can_delete(
+                                    node,
+                                    &state.graph,
+                                    user_state,
+                                );
+                                if can_delete {
+                                    responses.push(NodeResponse::DeleteNodeUi(node));
+                                }
+                            }
+                        }
+                    }
+                    egui::Event::Paste(text) => {
+                        // Text that doesn't contain copied nodes is ignored.
+                        if let Ok(contents) = ClipboardContents::from_text(&text) {
+                            let position =
+                                cursor_pos - state.pan_zoom.pan - editor_rect.min.to_vec2();
+                            for node in state.paste_in_open_graph(contents, position) {
+                                responses.push(NodeResponse::CreatedNode(node));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+        responses
+    }
+}
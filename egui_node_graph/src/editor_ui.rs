@@ -11,6 +11,9 @@ pub type PortLocations = std::collections::HashMap<AnyParameterId, Pos2>;
 pub type NodeRects = std::collections::HashMap<NodeId, Rect>;
 
 const DISTANCE_TO_CONNECT: f32 = 10.0;
+/// The vertical distance between the wires of an input port with multiple
+/// connections.
+const WIRE_SPACING: f32 = 8.0;
 
 /// Nodes communicate certain events to the parent graph when drawn. There is
 /// one special `User` variant which can be used by users as the return value
@@ -44,6 +47,64 @@ pub enum NodeResponse<UserResponse: UserResponseTrait, NodeData: NodeDataTrait>
         node: NodeId,
         drag_delta: Vec2,
     },
+    /// Emitted when the inline widget of an input parameter modifies its
+    /// value, as reported by [`UserResponseTrait::is_value_changed`]. The new
+    /// value is already stored in the graph.
+    InputValueChanged {
+        node: NodeId,
+        input: InputId,
+    },
+    /// Emitted when the selected nodes are collapsed into a new group node.
+    CreatedGroup(NodeId),
+    /// Emitted when the user asks to open a group node. The editor displays
+    /// the nested graph of the group from the next frame on.
+    OpenGroup(NodeId),
+    /// Emitted when the user bypasses a node, or enables it again. See
+    /// [`Node::bypassed`] and [`NodeDataTrait::can_bypass`].
+    SetBypassed {
+        node: NodeId,
+        bypassed: bool,
+    },
+    /// Emitted when the user drags the title bar of a frame. The nodes inside
+    /// the frame when the drag started move along with it.
+    MoveFrame {
+        frame: FrameId,
+        nodes: Vec<NodeId>,
+        drag_delta: Vec2,
+    },
+    /// Emitted when the user resizes a frame, with its new size.
+    ResizeFrame {
+        frame: FrameId,
+        size: Vec2,
+    },
+    /// Emitted when the user renames a frame or changes its color. The new
+    /// values are already stored in the frame.
+    EditFrame(FrameId),
+    /// Emitted when the user deletes a frame. The nodes inside it are kept.
+    DeleteFrame(FrameId),
+    /// Emitted when the user double clicks a wire, inserting a knot at
+    /// `index` in it. The `position` of the knot is in the same coordinates
+    /// as the node positions. See [`WireKnots`].
+    AddKnot {
+        output: OutputId,
+        input: InputId,
+        index: usize,
+        position: Pos2,
+    },
+    /// Emitted when the user drags the knot at `index` of a wire.
+    MoveKnot {
+        output: OutputId,
+        input: InputId,
+        index: usize,
+        drag_delta: Vec2,
+    },
+    /// Emitted when the user double clicks the knot at `index` of a wire,
+    /// removing it.
+    RemoveKnot {
+        output: OutputId,
+        input: InputId,
+        index: usize,
+    },
     User(UserResponse),
 }
 
@@ -61,6 +122,27 @@ pub struct GraphResponse<UserResponse: UserResponseTrait, NodeData: NodeDataTrai
     /// Is the mouse currently hovering the node finder?
     pub cursor_in_finder: bool,
 }
+impl<UserResponse: UserResponseTrait, NodeData: NodeDataTrait>
+    GraphResponse<UserResponse, NodeData>
+{
+    /// The nodes of `graph` at either end of the connections made or removed
+    /// during this frame.
+    pub(crate) fn reconnected_nodes<DataType, ValueType>(
+        &self,
+        graph: &Graph<NodeData, DataType, ValueType>,
+    ) -> HashSet<NodeId> {
+        let mut nodes = HashSet::new();
+        for response in &self.node_responses {
+            if let NodeResponse::ConnectEventEnded { output, input }
+            | NodeResponse::DisconnectEvent { output, input } = response
+            {
+                nodes.extend(graph.try_get_input(*input).map(|p| p.node));
+                nodes.extend(graph.outputs.get(*output).map(|p| p.node));
+            }
+        }
+        nodes
+    }
+}
 impl<UserResponse: UserResponseTrait, NodeData: NodeDataTrait> Default
     for GraphResponse<UserResponse, NodeData>
 {
@@ -79,6 +161,8 @@ pub struct GraphNodeWidget<'a, NodeData, DataType, ValueType> {
     pub node_rects: &'a mut NodeRects,
     pub node_id: NodeId,
     pub ongoing_drag: Option<(NodeId, AnyParameterId)>,
+    /// The ports the ongoing drag can be connected to.
+    pub drag_targets: &'a HashSet<AnyParameterId>,
     pub selected: bool,
     pub pan: egui::Vec2,
 }
@@ -112,6 +196,61 @@ where
         all_kinds: impl NodeTemplateIter<Item = NodeTemplate>,
         user_state: &mut UserState,
         prepend_responses: Vec<NodeResponse<UserResponse, NodeData>>,
+    ) -> GraphResponse<UserResponse, NodeData> {
+        let editor_rect = ui.max_rect();
+        let response = self.with_open_group(|state| {
+            state.draw_open_graph(ui, all_kinds, user_state, prepend_responses)
+        });
+
+        for node_response in &response.node_responses {
+            if let NodeResponse::OpenGroup(group_id) = node_response {
+                self.open_group(*group_id);
+            }
+        }
+        self.draw_group_breadcrumb(ui, editor_rect);
+
+        response
+    }
+
+    /// Draws the list of open groups at the top of the editor. Clicking one
+    /// of them goes back to it.
+    fn draw_group_breadcrumb(&mut self, ui: &mut Ui, editor_rect: Rect) {
+        if self.open_groups.is_empty() {
+            return;
+        }
+
+        let labels = self.open_group_labels();
+        let mut clicked_depth = None;
+        Area::new("group_breadcrumb")
+            .order(Order::Foreground)
+            .fixed_pos(editor_rect.min + vec2(8.0, 8.0))
+            .show(ui.ctx(), |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Root").clicked() {
+                        clicked_depth = Some(0);
+                    }
+                    for (depth, label) in labels.iter().enumerate() {
+                        ui.label("⏵");
+                        let is_open_group = depth + 1 == labels.len();
+                        if ui.add_enabled(!is_open_group, Button::new(label)).clicked() {
+                            clicked_depth = Some(depth + 1);
+                        }
+                    }
+                });
+            });
+        if let Some(depth) = clicked_depth {
+            self.close_groups_to(depth);
+        }
+    }
+
+    /// Draws the open graph: The nested graph of the innermost open group, or
+    /// the root graph. The open group must be swapped in.
+    fn draw_open_graph(
+        &mut self,
+        ui: &mut Ui,
+        all_kinds: impl NodeTemplateIter<Item = NodeTemplate>,
+        user_state: &mut UserState,
+        prepend_responses: Vec<NodeResponse<UserResponse, NodeData>>,
     ) -> GraphResponse<UserResponse, NodeData> {
         // This causes the graph editor to use as much free space as it can.
         // (so for windows it will use up to the resizeably set limit
@@ -147,6 +286,12 @@ where
         inconsistent self. It has either more or less values than the graph."
         );
 
+        // The ports the ongoing connection, if any, can be dropped onto.
+        let drag_targets = match self.connection_in_progress {
+            Some((_, origin)) => self.connection_targets(origin, user_state),
+            None => HashSet::new(),
+        };
+
         // Allocate rect before the nodes, otherwise this will block the interaction
         // with the nodes.
         let r = ui.allocate_rect(ui.min_rect(), Sense::click().union(Sense::drag()));
@@ -158,6 +303,10 @@ where
             drag_released_on_background = true;
         }
 
+        /* Draw frames, below the nodes */
+        let pan = self.pan_zoom.pan + editor_rect.min.to_vec2();
+        delayed_responses.extend(self.show_frames(ui, pan));
+
         /* Draw nodes */
         for node_id in self.node_order.iter().copied() {
             let responses = GraphNodeWidget {
@@ -167,6 +316,7 @@ where
                 node_rects: &mut node_rects,
                 node_id,
                 ongoing_drag: self.connection_in_progress,
+                drag_targets: &drag_targets,
                 selected: self
                     .selected_nodes
                     .iter()
@@ -188,11 +338,7 @@ where
             }
             node_finder_area.show(ui.ctx(), |ui| {
                 if let Some(node_kind) = node_finder.show(ui, all_kinds, user_state) {
-                    let new_node = self.graph.add_node(
-                        node_kind.node_graph_label(user_state),
-                        node_kind.user_data(user_state),
-                        |graph, node_id| node_kind.build_node(graph, user_state, node_id),
-                    );
+                    let new_node = self.graph.add_template_node(&node_kind, user_state);
                     self.node_positions.insert(
                         new_node,
                         cursor_pos - self.pan_zoom.pan - editor_rect.min.to_vec2(),
@@ -222,78 +368,89 @@ where
             let start_pos = port_locations[locator];
 
             // Find a port to connect to
-            fn snap_to_ports<
-                NodeData,
-                UserState,
-                DataType: DataTypeTrait<UserState>,
-                ValueType,
-                Key: slotmap::Key + Into<AnyParameterId>,
-                Value,
-            >(
-                graph: &Graph<NodeData, DataType, ValueType>,
-                port_type: &DataType,
-                ports: &SlotMap<Key, Value>,
+            fn snap_to_ports(
+                drag_targets: &HashSet<AnyParameterId>,
                 port_locations: &PortLocations,
                 cursor_pos: Pos2,
             ) -> Pos2 {
-                ports
+                drag_targets
                     .iter()
-                    .find_map(|(port_id, _)| {
-                        let compatible_ports = graph
-                            .any_param_type(port_id.into())
-                            .map(|other| other == port_type)
-                            .unwrap_or(false);
-
-                        if compatible_ports {
-                            port_locations.get(&port_id.into()).and_then(|port_pos| {
-                                if port_pos.distance(cursor_pos) < DISTANCE_TO_CONNECT {
-                                    Some(*port_pos)
-                                } else {
-                                    None
-                                }
-                            })
-                        } else {
-                            None
-                        }
+                    .find_map(|port_id| {
+                        port_locations.get(port_id).and_then(|port_pos| {
+                            if port_pos.distance(cursor_pos) < DISTANCE_TO_CONNECT {
+                                Some(*port_pos)
+                            } else {
+                                None
+                            }
+                        })
                     })
                     .unwrap_or(cursor_pos)
             }
 
+            let snapped_pos = snap_to_ports(&drag_targets, &port_locations, cursor_pos);
             let (src_pos, dst_pos) = match locator {
-                AnyParameterId::Output(_) => (
-                    start_pos,
-                    snap_to_ports(
-                        &self.graph,
-                        port_type,
-                        &self.graph.inputs,
-                        &port_locations,
-                        cursor_pos,
-                    ),
-                ),
-                AnyParameterId::Input(_) => (
-                    snap_to_ports(
-                        &self.graph,
-                        port_type,
-                        &self.graph.outputs,
-                        &port_locations,
-                        cursor_pos,
-                    ),
-                    start_pos,
-                ),
+                AnyParameterId::Output(_) => (start_pos, snapped_pos),
+                AnyParameterId::Input(_) => (snapped_pos, start_pos),
             };
-            draw_connection(ui.painter(), src_pos, dst_pos, connection_color);
+            draw_connection(ui.painter(), src_pos, &[], dst_pos, connection_color);
         }
 
-        for (input, output) in self.graph.iter_connections() {
-            let port_type = self
-                .graph
-                .any_param_type(AnyParameterId::Output(output))
-                .unwrap();
-            let connection_color = port_type.data_type_color(user_state);
-            let src_pos = port_locations[&AnyParameterId::Output(output)];
-            let dst_pos = port_locations[&AnyParameterId::Input(input)];
-            draw_connection(ui.painter(), src_pos, dst_pos, connection_color);
+        let mut wires = Vec::new();
+        for (input, outputs) in self.graph.connections.iter() {
+            let input_pos = port_locations[&AnyParameterId::Input(input)];
+            for (wire_index, output) in outputs.iter().copied().enumerate() {
+                let port_type = self
+                    .graph
+                    .any_param_type(AnyParameterId::Output(output))
+                    .unwrap();
+                let connection_color = port_type.data_type_color(user_state);
+                let src_pos = port_locations[&AnyParameterId::Output(output)];
+                let dst_pos = wire_endpoint(input_pos, wire_index, outputs.len());
+                let knots = self.knot_positions(output, input, pan);
+                draw_connection(ui.painter(), src_pos, &knots, dst_pos, connection_color);
+                wires.push(DrawnWire {
+                    output,
+                    input,
+                    src_pos,
+                    dst_pos,
+                    color: connection_color,
+                });
+            }
+        }
+        let double_click_pos = r.double_clicked().then_some(cursor_pos);
+        delayed_responses.extend(self.show_knots(ui, pan, &wires, double_click_pos));
+
+        /* Insert conversion nodes between ports of incompatible types */
+        let mut responses = Vec::with_capacity(delayed_responses.len());
+        for response in delayed_responses {
+            match response {
+                NodeResponse::ConnectEventEnded { output, input }
+                    if !self.graph[output]
+                        .typ
+                        .can_connect_to(&self.graph[input].typ, user_state) =>
+                {
+                    // The connection is replaced by the two connections of the
+                    // conversion node, if there's one for these types.
+                    if let Some((node, node_input, node_output)) =
+                        self.add_conversion_node(output, input, user_state)
+                    {
+                        responses.push(NodeResponse::CreatedNode(node));
+                        responses.push(NodeResponse::ConnectEventEnded {
+                            output,
+                            input: node_input,
+                        });
+                        responses.push(NodeResponse::ConnectEventEnded {
+                            output: node_output,
+                            input,
+                        });
+                    } else {
+                        responses.push(response);
+                    }
+                }
+                response => responses.push(response),
+            }
         }
+        let mut delayed_responses = responses;
 
         /* Handle responses from drawing nodes */
 
@@ -337,7 +494,7 @@ where
                 }
                 NodeResponse::DisconnectEvent { input, output } => {
                     let other_node = self.graph.get_output(*output).node;
-                    self.graph.remove_connection(*input);
+                    self.graph.disconnect(*output, *input);
                     self.connection_in_progress =
                         Some((other_node, AnyParameterId::Output(*output)));
                 }
@@ -361,6 +518,65 @@ where
                         }
                     }
                 }
+                NodeResponse::InputValueChanged { node, .. } => {
+                    self.graph.mark_dirty(*node);
+                }
+                NodeResponse::CreatedGroup(_) => {
+                    // Convenience NodeResponse for users
+                }
+                NodeResponse::OpenGroup(_) => {
+                    // Handled once the graph has been drawn
+                }
+                NodeResponse::SetBypassed { node, bypassed } => {
+                    self.graph.set_bypassed(*node, *bypassed);
+                }
+                NodeResponse::MoveFrame {
+                    frame,
+                    nodes,
+                    drag_delta,
+                } => {
+                    self.move_frame_in_open_graph(*frame, nodes, *drag_delta);
+                }
+                NodeResponse::ResizeFrame { frame, size } => {
+                    if let Some(frame) = self.frames.get_mut(*frame) {
+                        frame.size = *size;
+                    }
+                }
+                NodeResponse::EditFrame(_) => {
+                    // The frame was edited in place
+                }
+                NodeResponse::DeleteFrame(frame) => {
+                    self.remove_frame_in_open_graph(*frame);
+                }
+                NodeResponse::AddKnot {
+                    output,
+                    input,
+                    index,
+                    position,
+                } => {
+                    self.knots.insert(*output, *input, *index, *position);
+                }
+                NodeResponse::MoveKnot {
+                    output,
+                    input,
+                    index,
+                    drag_delta,
+                } => {
+                    if let Some(knot) = self
+                        .knots
+                        .get_mut(*output, *input)
+                        .and_then(|knots| knots.get_mut(*index))
+                    {
+                        *knot += *drag_delta;
+                    }
+                }
+                NodeResponse::RemoveKnot {
+                    output,
+                    input,
+                    index,
+                } => {
+                    self.knots.remove(*output, *input, *index);
+                }
                 NodeResponse::User(_) => {
                     // These are handled by the user code.
                 }
@@ -370,6 +586,9 @@ where
             }
         }
 
+        // Wires that were disconnected, here or by user code, lose their knots.
+        self.knots.retain_connections(&self.graph);
+
         // Handle box selection
         if let Some(box_start) = self.ongoing_box_selection {
             let selection_rect = Rect::from_two_pos(cursor_pos, box_start);
@@ -441,21 +660,250 @@ where
     }
 }
 
-fn draw_connection(painter: &Painter, src_pos: Pos2, dst_pos: Pos2, color: Color32) {
-    let connection_stroke = egui::Stroke { width: 5.0, color };
+impl<NodeData, DataType, ValueType, NodeTemplate, UserResponse, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+where
+    NodeData: NodeDataTrait<
+        Response = UserResponse,
+        UserState = UserState,
+        DataType = DataType,
+        ValueType = ValueType,
+    >,
+    UserResponse: UserResponseTrait,
+    DataType: DataTypeTrait<UserState>,
+    NodeTemplate: NodeTemplateTrait<
+        NodeData = NodeData,
+        DataType = DataType,
+        ValueType = ValueType,
+        UserState = UserState,
+    >,
+{
+    /// Returns the ports a connection being dragged from `origin` can be
+    /// dropped onto, taking into account the data types, the available
+    /// conversion nodes and the editor's [`CyclePolicy`].
+    pub fn connection_targets(
+        &self,
+        origin: AnyParameterId,
+        user_state: &mut UserState,
+    ) -> HashSet<AnyParameterId> {
+        let graph = &self.graph;
+        let origin_type = match graph.any_param_type(origin) {
+            Ok(typ) => typ,
+            Err(_) => return HashSet::new(),
+        };
 
-    let control_scale = ((dst_pos.x - src_pos.x) / 2.0).max(30.0);
-    let src_control = src_pos + Vec2::X * control_scale;
-    let dst_control = dst_pos - Vec2::X * control_scale;
+        // Only computed when cycles need special treatment
+        let cycle_forming_nodes = match self.cycle_policy {
+            CyclePolicy::Allow => HashSet::new(),
+            CyclePolicy::Forbid | CyclePolicy::AskUser => graph.cycle_forming_nodes(origin),
+        };
+        let allows_cycle = |output: OutputId, input: InputId, user_state: &mut UserState| {
+            let node = graph[input].node;
+            match self.cycle_policy {
+                CyclePolicy::Allow => true,
+                CyclePolicy::Forbid => false,
+                CyclePolicy::AskUser => graph[node]
+                    .user_data
+                    .allow_cycle(node, output, input, graph, user_state),
+            }
+        };
 
-    let bezier = CubicBezierShape::from_points_stroke(
-        [src_pos, src_control, dst_control, dst_pos],
-        false,
-        Color32::TRANSPARENT,
-        connection_stroke,
-    );
+        let connectable = |source: &DataType, target: &DataType, user_state: &mut UserState| {
+            source.can_connect_to(target, user_state)
+                || NodeTemplate::conversion_node(source, target, user_state).is_some()
+        };
+
+        let mut targets = HashSet::new();
+        match origin {
+            AnyParameterId::Output(output) => {
+                let origin_node = graph[output].node;
+                for (input, param) in graph.inputs.iter() {
+                    // Don't allow self-loops
+                    if param.node == origin_node
+                        || !connectable(origin_type, &param.typ, user_state)
+                    {
+                        continue;
+                    }
+                    if matches!(param.kind, InputParamKind::ConstantOnly) {
+                        continue;
+                    }
+                    // Parameters can't be connected twice
+                    if graph.input_connections(input).contains(&output) {
+                        continue;
+                    }
+                    if cycle_forming_nodes.contains(&param.node)
+                        && !allows_cycle(output, input, user_state)
+                    {
+                        continue;
+                    }
+                    targets.insert(AnyParameterId::Input(input));
+                }
+            }
+            AnyParameterId::Input(input) => {
+                let origin_node = graph[input].node;
+                let existing_connections = graph.input_connections(input);
+                for (output, param) in graph.outputs.iter() {
+                    // Don't allow self-loops
+                    if param.node == origin_node
+                        || !connectable(&param.typ, origin_type, user_state)
+                    {
+                        continue;
+                    }
+                    if existing_connections.contains(&output) {
+                        continue;
+                    }
+                    if cycle_forming_nodes.contains(&param.node)
+                        && !allows_cycle(output, input, user_state)
+                    {
+                        continue;
+                    }
+                    targets.insert(AnyParameterId::Output(output));
+                }
+            }
+        }
+        targets
+    }
 
-    painter.add(bezier);
+    /// Adds a node converting the values of `output` to the type of `input`,
+    /// placed halfway between their nodes. The node comes from
+    /// [`NodeTemplateTrait::conversion_node`], and is left unconnected.
+    /// Returns the node, with the input to connect `output` to and the output
+    /// to connect `input` to, or `None` when there's no suitable conversion.
+    pub fn add_conversion_node(
+        &mut self,
+        output: OutputId,
+        input: InputId,
+        user_state: &mut UserState,
+    ) -> Option<(NodeId, InputId, OutputId)> {
+        let template = NodeTemplate::conversion_node(
+            &self.graph[output].typ,
+            &self.graph[input].typ,
+            user_state,
+        )?;
+        let node = self.graph.add_template_node(&template, user_state);
+
+        let graph = &self.graph;
+        let node_input = graph[node].input_ids().find(|id| {
+            !matches!(graph[*id].kind, InputParamKind::ConstantOnly)
+                && graph[output]
+                    .typ
+                    .can_connect_to(&graph[*id].typ, user_state)
+        });
+        let node_output = graph[node]
+            .output_ids()
+            .find(|id| graph[*id].typ.can_connect_to(&graph[input].typ, user_state));
+        let (Some(node_input), Some(node_output)) = (node_input, node_output) else {
+            self.graph.remove_node(node);
+            return None;
+        };
+
+        let source_pos = self.node_positions.get(graph[output].node).copied();
+        let target_pos = self.node_positions.get(graph[input].node).copied();
+        let position = match (source_pos, target_pos) {
+            (Some(source), Some(target)) => source + (target - source) / 2.0,
+            (pos, None) | (None, pos) => pos.unwrap_or(Pos2::ZERO),
+        };
+        self.node_positions.insert(node, position);
+        self.node_order.push(node);
+        Some((node, node_input, node_output))
+    }
+}
+
+/// Returns the position where the wire at `wire_index` ends, for an input port
+/// at `port_pos` with `wire_count` connections. Wires of inputs with multiple
+/// connections are spread vertically so they can be told apart.
+fn wire_endpoint(port_pos: Pos2, wire_index: usize, wire_count: usize) -> Pos2 {
+    let offset = wire_index as f32 - (wire_count.max(1) - 1) as f32 / 2.0;
+    port_pos + vec2(0.0, offset * WIRE_SPACING)
+}
+
+/// Returns the lines of text of the tooltip of a port: The name of the
+/// parameter, the name of its data type and its number of connections. Returns
+/// `None` if the parameter is missing.
+fn port_tooltip_text<NodeData, DataType, ValueType, UserState>(
+    graph: &Graph<NodeData, DataType, ValueType>,
+    node_id: NodeId,
+    param_id: AnyParameterId,
+) -> Option<[String; 3]>
+where
+    DataType: DataTypeTrait<UserState>,
+{
+    let node = &graph[node_id];
+    let (name, connections) = match param_id {
+        AnyParameterId::Input(input) => (
+            node.inputs
+                .iter()
+                .find(|(_, id)| *id == input)
+                .map(|(name, _)| name.as_str()),
+            graph.input_connections(input).len(),
+        ),
+        AnyParameterId::Output(output) => (
+            node.outputs
+                .iter()
+                .find(|(_, id)| *id == output)
+                .map(|(name, _)| name.as_str()),
+            graph.outgoing(output).len(),
+        ),
+    };
+    let typ = graph.any_param_type(param_id).ok()?;
+    let connections = match connections {
+        0 => "Not connected".to_string(),
+        1 => "1 connection".to_string(),
+        n => format!("{n} connections"),
+    };
+    Some([
+        name.unwrap_or_default().to_string(),
+        typ.name().into_owned(),
+        connections,
+    ])
+}
+
+/// Draws the contents of the tooltip of a port: The text from
+/// [`port_tooltip_text`], and the extra contents from
+/// [`NodeDataTrait::port_tooltip_ui`].
+fn port_tooltip_ui<NodeData, DataType, ValueType, UserState>(
+    ui: &mut Ui,
+    graph: &Graph<NodeData, DataType, ValueType>,
+    node_id: NodeId,
+    param_id: AnyParameterId,
+    user_state: &mut UserState,
+) where
+    NodeData: NodeDataTrait<UserState = UserState, DataType = DataType, ValueType = ValueType>,
+    DataType: DataTypeTrait<UserState>,
+{
+    let Some([name, type_name, connections]) =
+        port_tooltip_text::<_, _, _, UserState>(graph, node_id, param_id)
+    else {
+        return;
+    };
+    ui.label(RichText::new(name).strong());
+    ui.label(type_name);
+    ui.label(connections);
+    graph[node_id]
+        .user_data
+        .port_tooltip_ui(ui, node_id, param_id, graph, user_state);
+}
+
+/// Draws a wire from `src_pos` to `dst_pos`, through the given knots. Each
+/// stretch of the wire between two points is drawn as a bezier curve.
+fn draw_connection(
+    painter: &Painter,
+    src_pos: Pos2,
+    knots: &[Pos2],
+    dst_pos: Pos2,
+    color: Color32,
+) {
+    let connection_stroke = egui::Stroke { width: 5.0, color };
+
+    for points in knots::wire_curves(src_pos, knots, dst_pos) {
+        let bezier = CubicBezierShape::from_points_stroke(
+            points,
+            false,
+            Color32::TRANSPARENT,
+            connection_stroke,
+        );
+        painter.add(bezier);
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -511,6 +959,18 @@ where
             text_color = color_from_hex("#505050").unwrap();
         }
 
+        // Bypassed nodes are drawn dimmed
+        let bypassed = self.graph[self.node_id].bypassed;
+        let dim = |color: Color32| {
+            if bypassed {
+                color.linear_multiply(0.5)
+            } else {
+                color
+            }
+        };
+        let background_color = dim(background_color);
+        let text_color = dim(text_color);
+
         ui.visuals_mut().widgets.noninteractive.fg_stroke = Stroke::new(2.0, text_color);
 
         // Preallocate shapes to paint below contents
@@ -562,6 +1022,29 @@ where
                         .top_bar_ui(ui, self.node_id, self.graph, user_state)
                         .into_iter(),
                 );
+                let bypassed = self.graph[self.node_id].bypassed;
+                if self.graph[self.node_id].user_data.can_bypass(
+                    self.node_id,
+                    self.graph,
+                    user_state,
+                ) && ui
+                    .selectable_label(bypassed, "Bypass")
+                    .on_hover_text("Disable this node, forwarding its inputs to its outputs")
+                    .clicked()
+                {
+                    responses.push(NodeResponse::SetBypassed {
+                        node: self.node_id,
+                        bypassed: !bypassed,
+                    });
+                }
+                if self.graph.is_group(self.node_id)
+                    && ui
+                        .small_button("Open")
+                        .on_hover_text("Edit the nodes inside this group")
+                        .clicked()
+                {
+                    responses.push(NodeResponse::OpenGroup(self.node_id));
+                }
                 ui.add_space(8.0); // The size of the little cross icon
             });
             ui.add_space(margin.y);
@@ -581,27 +1064,27 @@ where
                     // Default, but results in a totally safe alternative.
                     let mut value = std::mem::take(&mut self.graph[param_id].value);
 
-                    if self.graph.connection(param_id).is_some() {
-                        let node_responses = value.value_widget_connected(
+                    let node_responses = if self.graph.connection(param_id).is_some() {
+                        value.value_widget_connected(
                             &param_name,
                             self.node_id,
                             ui,
                             user_state,
                             &self.graph[self.node_id].user_data,
-                        );
-
-                        responses.extend(node_responses.into_iter().map(NodeResponse::User));
+                        )
                     } else {
-                        let node_responses = value.value_widget(
+                        value.value_widget(
                             &param_name,
                             self.node_id,
                             ui,
                             user_state,
                             &self.graph[self.node_id].user_data,
-                        );
-
-                        responses.extend(node_responses.into_iter().map(NodeResponse::User));
-                    }
+                        )
+                    };
+                    let value_changed = node_responses
+                        .iter()
+                        .any(UserResponseTrait::is_value_changed);
+                    responses.extend(node_responses.into_iter().map(NodeResponse::User));
 
                     self.graph[self.node_id].user_data.separator(
                         ui,
@@ -611,10 +1094,16 @@ where
                         user_state,
                     );
 
+                    if value_changed {
+                        responses.push(NodeResponse::InputValueChanged {
+                            node: self.node_id,
+                            input: param_id,
+                        });
+                    }
                     self.graph[param_id].value = value;
 
                     let height_after = ui.min_rect().bottom();
-                    input_port_heights.push((height_before + height_after) / 2.0);
+                    input_port_heights.push((param_id, (height_before + height_after) / 2.0));
                 }
             }
 
@@ -672,15 +1161,36 @@ where
             param_id: AnyParameterId,
             port_locations: &mut PortLocations,
             ongoing_drag: Option<(NodeId, AnyParameterId)>,
-            is_connected_input: bool,
+            drag_targets: &HashSet<AnyParameterId>,
         ) where
             DataType: DataTypeTrait<UserState>,
             UserResponse: UserResponseTrait,
-            NodeData: NodeDataTrait,
+            NodeData:
+                NodeDataTrait<UserState = UserState, DataType = DataType, ValueType = ValueType>,
         {
             let port_type = graph.any_param_type(param_id).unwrap();
 
-            let port_rect = Rect::from_center_size(port_pos, egui::vec2(10.0, 10.0));
+            // The wires ending at this port. Only inputs store connections.
+            let input_wires: &[OutputId] = match param_id {
+                AnyParameterId::Input(input) => graph.input_connections(input),
+                AnyParameterId::Output(_) => &[],
+            };
+            let is_multi_input = match param_id {
+                AnyParameterId::Input(input) => graph[input].cardinality == InputCardinality::Many,
+                AnyParameterId::Output(_) => false,
+            };
+
+            // Inputs accepting multiple connections are drawn taller, leaving
+            // room for the endpoint of each wire.
+            let port_size = if is_multi_input {
+                egui::vec2(
+                    10.0,
+                    10.0 + (input_wires.len().max(2) - 1) as f32 * WIRE_SPACING,
+                )
+            } else {
+                egui::vec2(10.0, 10.0)
+            };
+            let port_rect = Rect::from_center_size(port_pos, port_size);
 
             let sense = if ongoing_drag.is_some() {
                 Sense::hover()
@@ -688,28 +1198,59 @@ where
                 Sense::click_and_drag()
             };
 
-            let resp = ui.allocate_rect(port_rect, sense);
+            let mut resp = ui.allocate_rect(port_rect, sense);
+            if ongoing_drag.is_none() {
+                resp = resp.on_hover_ui(|ui| {
+                    port_tooltip_ui(ui, graph, node_id, param_id, user_state);
+                });
+            }
 
             // Check if the distance between the port and the mouse is the distance to connect
             let close_enough = if let Some(pointer_pos) = ui.ctx().pointer_hover_pos() {
-                port_rect.center().distance(pointer_pos) < DISTANCE_TO_CONNECT
+                if is_multi_input {
+                    port_rect.distance_to_pos(pointer_pos) < DISTANCE_TO_CONNECT / 2.0
+                } else {
+                    port_rect.center().distance(pointer_pos) < DISTANCE_TO_CONNECT
+                }
             } else {
                 false
             };
 
+            // Whether the ongoing drag can be connected to this port
+            let is_drag_target = drag_targets.contains(&param_id);
+            let is_drag_origin = ongoing_drag.map(|(_, origin)| origin) == Some(param_id);
+
             let port_color = if close_enough {
-                Color32::WHITE
+                if ongoing_drag.is_some() && !is_drag_target && !is_drag_origin {
+                    // Let the user know the connection will be rejected
+                    color_from_hex("#d04848").unwrap()
+                } else {
+                    Color32::WHITE
+                }
             } else {
                 port_type.data_type_color(user_state)
             };
-            ui.painter()
-                .circle(port_rect.center(), 5.0, port_color, Stroke::NONE);
+            if is_multi_input {
+                ui.painter().rect_filled(port_rect, 5.0, port_color);
+            } else {
+                ui.painter()
+                    .circle(port_rect.center(), 5.0, port_color, Stroke::NONE);
+            }
 
             if resp.drag_started() {
-                if is_connected_input {
-                    let input = param_id.assume_input();
-                    let corresp_output = graph
-                        .connection(input)
+                if !input_wires.is_empty() {
+                    // Dragging from a connected input detaches the wire closest
+                    // to the point where the drag started.
+                    let press_pos = ui
+                        .input(|i| i.pointer.press_origin())
+                        .unwrap_or(port_rect.center());
+                    let wire_distance = |wire_index: usize| {
+                        wire_endpoint(port_rect.center(), wire_index, input_wires.len())
+                            .distance(press_pos)
+                    };
+                    let corresp_output = (0..input_wires.len())
+                        .min_by(|a, b| wire_distance(*a).total_cmp(&wire_distance(*b)))
+                        .map(|wire_index| input_wires[wire_index])
                         .expect("Connection data should be valid");
                     responses.push(NodeResponse::DisconnectEvent {
                         input: param_id.assume_input(),
@@ -720,20 +1261,16 @@ where
                 }
             }
 
-            if let Some((origin_node, origin_param)) = ongoing_drag {
-                if origin_node != node_id {
-                    // Don't allow self-loops
-                    if graph.any_param_type(origin_param).unwrap() == port_type
-                        && close_enough
-                        && ui.input(|i| i.pointer.any_released())
-                    {
-                        match (param_id, origin_param) {
-                            (AnyParameterId::Input(input), AnyParameterId::Output(output))
-                            | (AnyParameterId::Output(output), AnyParameterId::Input(input)) => {
-                                responses.push(NodeResponse::ConnectEventEnded { input, output });
-                            }
-                            _ => { /* Ignore in-in or out-out connections */ }
+            if let Some((_, origin_param)) = ongoing_drag {
+                // Self-loops, type mismatches and rejected cycles are already
+                // filtered out of the drag targets.
+                if is_drag_target && close_enough && ui.input(|i| i.pointer.any_released()) {
+                    match (param_id, origin_param) {
+                        (AnyParameterId::Input(input), AnyParameterId::Output(output))
+                        | (AnyParameterId::Output(output), AnyParameterId::Input(input)) => {
+                            responses.push(NodeResponse::ConnectEventEnded { input, output });
                         }
+                        _ => { /* Ignore in-in or out-out connections */ }
                     }
                 }
             }
@@ -741,13 +1278,9 @@ where
             port_locations.insert(param_id, port_rect.center());
         }
 
-        // Input ports
-        for ((_, param), port_height) in self.graph[self.node_id]
-            .inputs
-            .iter()
-            .zip(input_port_heights.into_iter())
-        {
-            let should_draw = match self.graph[*param].kind() {
+        // Input ports. Inputs that aren't shown inline have no port.
+        for (param, port_height) in input_port_heights {
+            let should_draw = match self.graph[param].kind() {
                 InputParamKind::ConnectionOnly => true,
                 InputParamKind::ConstantOnly => false,
                 InputParamKind::ConnectionOrConstant => true,
@@ -762,10 +1295,10 @@ where
                     user_state,
                     pos_left,
                     &mut responses,
-                    AnyParameterId::Input(*param),
+                    AnyParameterId::Input(param),
                     self.port_locations,
                     self.ongoing_drag,
-                    self.graph.connection(*param).is_some(),
+                    self.drag_targets,
                 );
             }
         }
@@ -787,7 +1320,7 @@ where
                 AnyParameterId::Output(*param),
                 self.port_locations,
                 self.ongoing_drag,
-                false,
+                self.drag_targets,
             );
         }
 
@@ -805,10 +1338,10 @@ where
             let titlebar = Shape::Rect(RectShape {
                 rect: titlebar_rect,
                 rounding,
-                fill: self.graph[self.node_id]
+                fill: dim(self.graph[self.node_id]
                     .user_data
                     .titlebar_color(ui, self.node_id, self.graph, user_state)
-                    .unwrap_or_else(|| background_color.lighten(0.8)),
+                    .unwrap_or_else(|| background_color.lighten(0.8))),
                 stroke: Stroke::NONE,
             });
 
@@ -935,3 +1468,270 @@ where
         resp
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{self, Type};
+
+    #[derive(Clone, Debug)]
+    struct TestResponse;
+
+    impl UserResponseTrait for TestResponse {}
+
+    /// Node data accepting cycle-forming connections when `allow_cycle` is
+    /// set, see [`CyclePolicy::AskUser`].
+    struct TestNodeData {
+        allow_cycle: bool,
+    }
+
+    impl NodeDataTrait for TestNodeData {
+        type Response = TestResponse;
+        type UserState = ();
+        type DataType = Type;
+        type ValueType = ();
+
+        fn bottom_ui(
+            &self,
+            _ui: &mut Ui,
+            _node_id: NodeId,
+            _graph: &Graph<Self, Type, ()>,
+            _user_state: &mut (),
+        ) -> Vec<NodeResponse<TestResponse, Self>> {
+            vec![]
+        }
+
+        fn allow_cycle(
+            &self,
+            _node_id: NodeId,
+            _output: OutputId,
+            _input: InputId,
+            _graph: &Graph<Self, Type, ()>,
+            _user_state: &mut (),
+        ) -> bool {
+            self.allow_cycle
+        }
+    }
+
+    /// The only template converts floats to vectors. Its first input only
+    /// takes constants, and it has a variadic input after its other inputs.
+    #[derive(Clone, Copy)]
+    struct Conversion;
+
+    impl NodeTemplateTrait for Conversion {
+        type NodeData = TestNodeData;
+        type DataType = Type;
+        type ValueType = ();
+        type UserState = ();
+        type CategoryType = ();
+
+        fn node_finder_label(&self, _user_state: &mut ()) -> std::borrow::Cow<'_, str> {
+            std::borrow::Cow::Borrowed("Conversion")
+        }
+
+        fn node_graph_label(&self, _user_state: &mut ()) -> String {
+            "Conversion".into()
+        }
+
+        fn user_data(&self, _user_state: &mut ()) -> TestNodeData {
+            TestNodeData { allow_cycle: false }
+        }
+
+        fn build_node(
+            &self,
+            graph: &mut Graph<TestNodeData, Type, ()>,
+            _user_state: &mut (),
+            node_id: NodeId,
+        ) {
+            graph.add_input_param(
+                node_id,
+                "scale".into(),
+                Type::Float,
+                (),
+                InputParamKind::ConstantOnly,
+                true,
+            );
+            graph.add_input_param(
+                node_id,
+                "in".into(),
+                Type::Float,
+                (),
+                InputParamKind::ConnectionOrConstant,
+                true,
+            );
+            graph.add_output_param(node_id, "out".into(), Type::Vec2);
+        }
+
+        fn variadic_inputs(&self, _user_state: &mut ()) -> Vec<VariadicInput<Type, ()>> {
+            vec![VariadicInput {
+                name: "extra".into(),
+                typ: Type::Text,
+                value: (),
+                kind: InputParamKind::ConnectionOnly,
+                shown_inline: true,
+            }]
+        }
+
+        fn conversion_node(source: &Type, target: &Type, _user_state: &mut ()) -> Option<Self> {
+            (*source == Type::Float && *target == Type::Vec2).then_some(Conversion)
+        }
+    }
+
+    type TestEditor = GraphEditorState<TestNodeData, Type, (), Conversion, ()>;
+
+    fn add_node(editor: &mut TestEditor, inputs: &[(&str, Type, InputParamKind)]) -> NodeId {
+        editor.graph.add_node(
+            "node".into(),
+            TestNodeData { allow_cycle: false },
+            |graph, node_id| {
+                for (name, typ, kind) in inputs {
+                    graph.add_input_param(node_id, name.to_string(), *typ, (), *kind, true);
+                }
+                graph.add_output_param(node_id, "out".into(), Type::Float);
+            },
+        )
+    }
+
+    #[test]
+    pub fn test_connection_targets() {
+        use InputParamKind::*;
+
+        let mut editor = TestEditor::default();
+        let a = add_node(&mut editor, &[]);
+        let b = add_node(
+            &mut editor,
+            &[
+                ("x", Type::Float, ConnectionOrConstant),
+                ("v", Type::Vec2, ConnectionOnly),
+                ("k", Type::Float, ConstantOnly),
+                ("t", Type::Text, ConnectionOnly),
+            ],
+        );
+        let c = add_node(&mut editor, &[("x", Type::Float, ConnectionOnly)]);
+        let d = add_node(&mut editor, &[("x", Type::Float, ConnectionOnly)]);
+        let graph = &mut editor.graph;
+        let a_out = graph[a].get_output("out").unwrap();
+        let b_out = graph[b].get_output("out").unwrap();
+        let c_out = graph[c].get_output("out").unwrap();
+        let d_out = graph[d].get_output("out").unwrap();
+        let b_x = graph[b].get_input("x").unwrap();
+        let b_v = graph[b].get_input("v").unwrap();
+        let c_x = graph[c].get_input("x").unwrap();
+        let d_x = graph[d].get_input("x").unwrap();
+        graph.add_connection(a_out, b_x);
+        graph.add_connection(b_out, c_x);
+
+        // Inputs taking a conversion node are targets, existing connections,
+        // constants and incompatible types are not
+        assert_eq!(
+            editor.connection_targets(a_out.into(), &mut ()),
+            HashSet::from([b_v.into(), c_x.into(), d_x.into()])
+        );
+        assert_eq!(
+            editor.connection_targets(c_x.into(), &mut ()),
+            HashSet::from([a_out.into(), d_out.into()])
+        );
+
+        // Cycle-forming targets depend on the policy
+        assert_eq!(
+            editor.connection_targets(c_out.into(), &mut ()),
+            HashSet::from([b_x.into(), b_v.into(), d_x.into()])
+        );
+        editor.cycle_policy = CyclePolicy::Forbid;
+        assert_eq!(
+            editor.connection_targets(c_out.into(), &mut ()),
+            HashSet::from([d_x.into()])
+        );
+        assert_eq!(
+            editor.connection_targets(b_x.into(), &mut ()),
+            HashSet::from([d_out.into()])
+        );
+        editor.cycle_policy = CyclePolicy::AskUser;
+        editor.graph[b].user_data.allow_cycle = true;
+        assert_eq!(
+            editor.connection_targets(c_out.into(), &mut ()),
+            HashSet::from([b_x.into(), b_v.into(), d_x.into()])
+        );
+        assert_eq!(
+            editor.connection_targets(b_x.into(), &mut ()),
+            HashSet::from([c_out.into(), d_out.into()])
+        );
+        assert_eq!(
+            editor.connection_targets(c_x.into(), &mut ()),
+            HashSet::from([a_out.into(), d_out.into()])
+        );
+    }
+
+    #[test]
+    pub fn test_add_conversion_node() {
+        let mut editor = TestEditor::default();
+        let a = add_node(&mut editor, &[]);
+        let b = add_node(
+            &mut editor,
+            &[
+                ("v", Type::Vec2, InputParamKind::ConnectionOnly),
+                ("t", Type::Text, InputParamKind::ConnectionOnly),
+            ],
+        );
+        editor.node_positions.insert(a, pos2(0.0, 0.0));
+        editor.node_positions.insert(b, pos2(100.0, 50.0));
+        let a_out = editor.graph[a].get_output("out").unwrap();
+        let b_v = editor.graph[b].get_input("v").unwrap();
+        let b_t = editor.graph[b].get_input("t").unwrap();
+
+        assert_eq!(editor.add_conversion_node(a_out, b_t, &mut ()), None);
+        assert_eq!(editor.graph.nodes.len(), 2);
+
+        // The constant input of the conversion node is skipped
+        let (node, input, output) = editor.add_conversion_node(a_out, b_v, &mut ()).unwrap();
+        assert_eq!(input, editor.graph[node].get_input("in").unwrap());
+        assert_eq!(output, editor.graph[node].get_output("out").unwrap());
+        assert_eq!(editor.node_positions[node], pos2(50.0, 25.0));
+        assert_eq!(editor.node_order, vec![node]);
+
+        // The declared variadic input follows the inputs of `build_node`
+        let names: Vec<&str> = editor.graph[node]
+            .inputs
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, vec!["scale", "in", "extra 1"]);
+        assert_eq!(editor.graph.variadic_inputs(node, "extra").len(), 1);
+    }
+
+    #[test]
+    pub fn test_port_tooltip_text() {
+        let mut graph = test_utils::TestGraph::new();
+        let a = test_utils::add_node(&mut graph, &[]);
+        let b = test_utils::add_node(&mut graph, &["in"]);
+        let c = test_utils::add_typed_node(&mut graph, Type::Text, &["in"]);
+        let a_out = graph[a].get_output("out").unwrap();
+        let b_in = graph[b].get_input("in").unwrap();
+        let c_in = graph[c].get_input("in").unwrap();
+        test_utils::connect(&mut graph, a, b, "in");
+        let text = |graph: &test_utils::TestGraph, node, param: AnyParameterId| {
+            port_tooltip_text::<_, _, _, ()>(graph, node, param).map(|lines| lines.to_vec())
+        };
+
+        assert_eq!(
+            text(&graph, b, b_in.into()).unwrap(),
+            vec!["in", "float", "1 connection"]
+        );
+        assert_eq!(
+            text(&graph, c, c_in.into()).unwrap(),
+            vec!["in", "text", "Not connected"]
+        );
+        assert_eq!(
+            text(&graph, a, a_out.into()).unwrap(),
+            vec!["out", "float", "1 connection"]
+        );
+        test_utils::connect(&mut graph, a, c, "in");
+        assert_eq!(
+            text(&graph, a, a_out.into()).unwrap(),
+            vec!["out", "float", "2 connections"]
+        );
+
+        graph.remove_input_param(c_in);
+        assert_eq!(text(&graph, c, c_in.into()), None);
+    }
+}
@@ -7,4 +7,104 @@ pub enum EguiGraphError {
 
     #[error("Parameter {0:?} was not found in the graph.")]
     InvalidParameterId(AnyParameterId),
+
+    #[error("The graph contains a cycle through nodes {:?}", .0.nodes)]
+    CycleDetected(Cycle),
+
+    #[error("Node {node:?} lists parameter {param:?}, which is not in the graph")]
+    MissingParameter { node: NodeId, param: AnyParameterId },
+
+    #[error("Parameter {param:?} is listed by node {node:?}, but refers to node {owner:?}")]
+    WrongParameterNode {
+        param: AnyParameterId,
+        node: NodeId,
+        owner: NodeId,
+    },
+
+    #[error("Parameter {0:?} is not listed by the node it refers to")]
+    OrphanParameter(AnyParameterId),
+
+    #[error("Node {0:?} has more than one parameter named {1}")]
+    DuplicateParameterName(NodeId, String),
+
+    #[error("The connection from {output:?} to {input:?} refers to a missing parameter")]
+    DanglingConnection { input: InputId, output: OutputId },
+
+    #[error("Input {0:?} only accepts constants, but has connections")]
+    ConnectedConstantInput(InputId),
+
+    #[error("Input {0:?} accepts a single connection, but has several")]
+    TooManyConnections(InputId),
+
+    #[error("The type of output {output:?} can't be connected to input {input:?}")]
+    TypeMismatch { input: InputId, output: OutputId },
+
+    #[error("The outgoing connections of output {0:?} don't match the connections of the graph")]
+    InconsistentOutgoingConnections(OutputId),
+
+    #[error("The graph has a group for node {0:?}, which is not in the graph")]
+    OrphanGroup(NodeId),
+
+    #[error("Port {port:?} of group {group:?} can't forward the nested parameter {inner:?}")]
+    GroupPortTypeMismatch {
+        group: NodeId,
+        port: AnyParameterId,
+        inner: AnyParameterId,
+    },
+
+    /// A problem found in the nested graph of a group node. The ids in the
+    /// error refer to the nested graph.
+    #[error("In group {group:?}: {error}")]
+    InGroup {
+        group: NodeId,
+        error: Box<EguiGraphError>,
+    },
+
+    #[error("Node {0:?} is missing from the node order")]
+    MissingFromNodeOrder(NodeId),
+
+    #[error("The node order contains an unknown or repeated node {0:?}")]
+    InvalidNodeOrder(NodeId),
+
+    #[error("Node {0:?} has no position")]
+    MissingNodePosition(NodeId),
+
+    #[error("Frame {0:?} is missing from the frame order")]
+    MissingFromFrameOrder(FrameId),
+
+    #[error("The frame order contains an unknown or repeated frame {0:?}")]
+    InvalidFrameOrder(FrameId),
+
+    #[error("The open group {0:?} is not a group of the enclosing graph")]
+    InvalidOpenGroup(NodeId),
+
+    #[error("The graph file contains an unknown or repeated node id {0}")]
+    InvalidFileNode(u32),
+
+    #[error("The node order of the graph file does not list the node id {0}")]
+    UnorderedFileNode(u32),
+
+    #[cfg(feature = "persistence")]
+    #[error("Could not read or write the clipboard contents: {0}")]
+    Clipboard(String),
+
+    #[cfg(feature = "persistence")]
+    #[error("Could not save or load the graph: {0}")]
+    Persistence(String),
+
+    #[cfg(feature = "persistence")]
+    #[error("The saved graph has version {found} of the {format}, but only up to {supported} is supported")]
+    NewerVersion {
+        format: SavedFormat,
+        found: u32,
+        supported: u32,
+    },
+
+    #[cfg(feature = "persistence")]
+    #[error("Could not migrate the saved graph from version {version} of the {format}: {message}")]
+    Migration {
+        format: SavedFormat,
+        version: u32,
+        message: String,
+    },
 }
@@ -0,0 +1,697 @@
+use super::*;
+use std::collections::{HashMap, HashSet};
+
+/// This trait can be implemented by the `NodeData` generic parameter of the
+/// [`Graph`] to evaluate the graph using [`Graph::evaluate_node`] and
+/// [`Graph::evaluate_output`]. The library takes care of evaluating the
+/// dependencies of each node in the right order, resolving connected and
+/// inline input values, and caching the results.
+pub trait NodeEvaluatorTrait: Sized {
+    /// Must be set to the custom user `DataType` type
+    type DataType;
+    /// Must be set to the custom user `ValueType` type
+    type ValueType;
+    /// The values flowing through the connections of the graph. Inline values
+    /// of unconnected inputs are converted to this type using its `From`
+    /// implementation, so this can simply be set to the `ValueType`.
+    type Value: Clone + From<Self::ValueType>;
+    /// Custom state available to every node during evaluation. Use `()` if
+    /// you don't need it.
+    type EvalState;
+
+    /// Computes the outputs of this node from the values of its inputs. All
+    /// the nodes connected to the inputs have already been evaluated.
+    ///
+    /// This is not called for group nodes, whose outputs are computed by
+    /// evaluating their nested graph, see [`Graph::evaluate_node`].
+    fn evaluate(
+        &self,
+        node_id: NodeId,
+        graph: &Graph<Self, Self::DataType, Self::ValueType>,
+        inputs: &NodeInputs<Self::Value>,
+        state: &mut Self::EvalState,
+    ) -> Result<NodeOutputs<Self::Value>, NodeEvalError>;
+
+    /// Declares how the values flow through this node while it is bypassed,
+    /// as `(input, output)` pairs of parameter names: Each listed output takes
+    /// the value of its input, or its first value for inputs with multiple
+    /// connections. Outputs that are not listed, or whose input has no value,
+    /// produce no value. See [`Node::bypassed`].
+    ///
+    /// The default forwards nothing.
+    fn pass_through(
+        &self,
+        _node_id: NodeId,
+        _graph: &Graph<Self, Self::DataType, Self::ValueType>,
+    ) -> Vec<(String, String)> {
+        Vec::new()
+    }
+}
+
+/// The input values of a node, passed to [`NodeEvaluatorTrait::evaluate`].
+#[derive(Clone, Debug)]
+pub struct NodeInputs<Value> {
+    /// The values of each input parameter, by name. Holds one value per
+    /// connection, or the inline value when the input is not connected.
+    pub values: Vec<(String, Vec<Value>)>,
+}
+
+impl<Value> NodeInputs<Value> {
+    /// Returns all the values of the input named `name`. This is useful for
+    /// inputs with [`InputCardinality::Many`]. The slice is empty for inputs
+    /// without connections that don't accept a constant value.
+    pub fn get_all(&self, name: &str) -> Result<&[Value], NodeEvalError> {
+        self.values
+            .iter()
+            .find(|(param_name, _)| param_name == name)
+            .map(|(_, values)| values.as_slice())
+            .ok_or_else(|| NodeEvalError::UnknownInput(name.into()))
+    }
+
+    /// Returns the value of the input named `name`. For inputs with multiple
+    /// connections, this is the value of the first connection.
+    pub fn get(&self, name: &str) -> Result<&Value, NodeEvalError> {
+        self.get_all(name)?
+            .first()
+            .ok_or_else(|| NodeEvalError::Unconnected(name.into()))
+    }
+}
+
+/// The output values of a node, returned by [`NodeEvaluatorTrait::evaluate`].
+#[derive(Clone, Debug)]
+pub struct NodeOutputs<Value> {
+    pub values: Vec<(String, Value)>,
+}
+
+impl<Value> Default for NodeOutputs<Value> {
+    fn default() -> Self {
+        Self { values: Vec::new() }
+    }
+}
+
+impl<Value> NodeOutputs<Value> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the value of the output named `name`.
+    pub fn set(&mut self, name: impl Into<String>, value: Value) {
+        self.values.push((name.into(), value));
+    }
+
+    /// Builder-style variant of [`NodeOutputs::set`].
+    pub fn with(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.set(name, value);
+        self
+    }
+}
+
+/// An error evaluating a single node. Returned by
+/// [`NodeEvaluatorTrait::evaluate`], and wrapped in an [`EvaluationError`]
+/// that identifies the failing node.
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum NodeEvalError {
+    #[error("There is no input named {0}")]
+    UnknownInput(String),
+
+    #[error("Input {0} is not connected")]
+    Unconnected(String),
+
+    #[error("There is no output named {0}")]
+    UnknownOutput(String),
+
+    #[error("No value was produced for output {0}")]
+    MissingOutput(String),
+
+    /// The evaluation of the nested graph of a group node failed. The ids in
+    /// the error refer to the nested graph.
+    #[error("{0}")]
+    InGroup(Box<EvaluationError>),
+
+    /// A custom error, produced by user code.
+    #[error("{message}")]
+    Custom {
+        /// The parameter that caused the error, if any.
+        param: Option<String>,
+        message: String,
+    },
+}
+
+impl NodeEvalError {
+    pub fn custom(message: impl Into<String>) -> Self {
+        Self::Custom {
+            param: None,
+            message: message.into(),
+        }
+    }
+
+    /// Attaches the name of the parameter that caused a custom error.
+    pub fn with_param(self, name: impl Into<String>) -> Self {
+        match self {
+            Self::Custom { message, .. } => Self::Custom {
+                param: Some(name.into()),
+                message,
+            },
+            other => other,
+        }
+    }
+
+    /// The name of the parameter this error refers to, if any.
+    pub fn param(&self) -> Option<&str> {
+        match self {
+            NodeEvalError::UnknownInput(name)
+            | NodeEvalError::Unconnected(name)
+            | NodeEvalError::UnknownOutput(name)
+            | NodeEvalError::MissingOutput(name) => Some(name),
+            NodeEvalError::Custom { param, .. } => param.as_deref(),
+            NodeEvalError::InGroup(_) => None,
+        }
+    }
+}
+
+/// An error produced when evaluating a graph.
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum EvaluationError {
+    #[error("Error evaluating node '{label}' ({node:?}): {error}")]
+    Node {
+        node: NodeId,
+        /// The label of the failing node, for display purposes.
+        label: String,
+        error: NodeEvalError,
+    },
+
+    #[error("The evaluated nodes depend on a cycle through nodes {:?}", .0.nodes)]
+    Cycle(Cycle),
+}
+
+impl EvaluationError {
+    /// The node that failed to evaluate, if the error is specific to a node.
+    pub fn node(&self) -> Option<NodeId> {
+        match self {
+            EvaluationError::Node { node, .. } => Some(*node),
+            EvaluationError::Cycle(_) => None,
+        }
+    }
+
+    /// The name of the parameter that caused the error, if known.
+    pub fn param(&self) -> Option<&str> {
+        match self {
+            EvaluationError::Node { error, .. } => error.param(),
+            EvaluationError::Cycle(_) => None,
+        }
+    }
+}
+
+/// Stores the values of the outputs computed during evaluation, so each node
+/// is only evaluated once. The cache can be kept across evaluations as long
+/// as the graph doesn't change, or cleared to evaluate everything again.
+#[derive(Clone, Debug)]
+pub struct OutputsCache<Value> {
+    pub values: HashMap<OutputId, Value>,
+    /// The nodes that have been evaluated. Nodes may not produce a value
+    /// for all of their outputs, so this is tracked separately.
+    pub evaluated_nodes: HashSet<NodeId>,
+}
+
+impl<Value> Default for OutputsCache<Value> {
+    fn default() -> Self {
+        Self {
+            values: HashMap::new(),
+            evaluated_nodes: HashSet::new(),
+        }
+    }
+}
+
+impl<Value> OutputsCache<Value> {
+    pub fn get(&self, output: OutputId) -> Option<&Value> {
+        self.values.get(&output)
+    }
+
+    pub fn is_evaluated(&self, node: NodeId) -> bool {
+        self.evaluated_nodes.contains(&node)
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.evaluated_nodes.clear();
+    }
+
+    /// Discards the results of the given nodes, so they are evaluated again
+    /// the next time they are needed. Results of outputs that no longer
+    /// exist in the graph are discarded too.
+    ///
+    /// This is meant to be used together with the dirty tracking of the
+    /// [`Graph`], so that only the affected part of the graph is recomputed:
+    ///
+    /// ```ignore
+    /// let dirty = graph.take_dirty_nodes();
+    /// cache.invalidate(&graph, &dirty);
+    /// graph.evaluate_node(node_id, &mut cache, &mut state)?;
+    /// ```
+    pub fn invalidate<NodeData, DataType, ValueType>(
+        &mut self,
+        graph: &Graph<NodeData, DataType, ValueType>,
+        nodes: &HashSet<NodeId>,
+    ) {
+        self.evaluated_nodes.retain(|node| !nodes.contains(node));
+        self.values.retain(|output, _| {
+            graph
+                .try_get_output(*output)
+                .is_some_and(|param| !nodes.contains(&param.node))
+        });
+    }
+}
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType>
+where
+    NodeData: NodeEvaluatorTrait<DataType = DataType, ValueType = ValueType>,
+    ValueType: Clone,
+{
+    /// Evaluates `node_id`, after evaluating all the nodes it depends on. The
+    /// results are stored in the `cache`, and nodes that were already
+    /// evaluated are not evaluated again. Bypassed nodes aren't evaluated,
+    /// their inputs are forwarded instead, see
+    /// [`NodeEvaluatorTrait::pass_through`].
+    ///
+    /// Group nodes are evaluated through their nested graph: The values of
+    /// each input of the group node are fed to the nested inputs it stands
+    /// for, and each output of the group node takes the value of the nested
+    /// output it forwards. See [`NodeGroup`].
+    pub fn evaluate_node(
+        &self,
+        node_id: NodeId,
+        cache: &mut OutputsCache<NodeData::Value>,
+        state: &mut NodeData::EvalState,
+    ) -> Result<(), EvaluationError> {
+        self.evaluate_node_fed(node_id, &HashMap::new(), cache, state)
+    }
+
+    /// Like [`Graph::evaluate_node`], but the unconnected inputs listed in
+    /// `fed_inputs` take the given values instead of their inline value.
+    fn evaluate_node_fed(
+        &self,
+        node_id: NodeId,
+        fed_inputs: &HashMap<InputId, Vec<NodeData::Value>>,
+        cache: &mut OutputsCache<NodeData::Value>,
+        state: &mut NodeData::EvalState,
+    ) -> Result<(), EvaluationError> {
+        for node in self.evaluation_order(node_id, cache)? {
+            let inputs = self.node_inputs(node, fed_inputs, cache)?;
+            let node_error = |error| EvaluationError::Node {
+                node,
+                label: self[node].label.clone(),
+                error,
+            };
+
+            let outputs = if self[node].bypassed {
+                self.pass_through_outputs(node, &inputs)
+                    .map_err(node_error)?
+            } else if let Some(group) = self.groups.get(node) {
+                self.group_outputs(node, group, &inputs, state)
+                    .map_err(node_error)?
+            } else {
+                self[node]
+                    .user_data
+                    .evaluate(node, self, &inputs, state)
+                    .map_err(node_error)?
+            };
+            for (name, value) in outputs.values {
+                let output = self[node]
+                    .get_output(&name)
+                    .map_err(|_| node_error(NodeEvalError::UnknownOutput(name)))?;
+                cache.values.insert(output, value);
+            }
+            cache.evaluated_nodes.insert(node);
+        }
+        Ok(())
+    }
+
+    /// Evaluates the node containing `output` and returns the value of the
+    /// output.
+    pub fn evaluate_output(
+        &self,
+        output: OutputId,
+        cache: &mut OutputsCache<NodeData::Value>,
+        state: &mut NodeData::EvalState,
+    ) -> Result<NodeData::Value, EvaluationError> {
+        self.evaluate_node(self[output].node, cache, state)?;
+        self.cached_output(output, cache).cloned()
+    }
+
+    /// Returns the nodes that need to be evaluated in order to evaluate
+    /// `node_id`, dependencies first. Nodes already in the cache are skipped.
+    fn evaluation_order(
+        &self,
+        node_id: NodeId,
+        cache: &OutputsCache<NodeData::Value>,
+    ) -> Result<Vec<NodeId>, EvaluationError> {
+        if cache.is_evaluated(node_id) {
+            return Ok(vec![]);
+        }
+        let dependencies = |node: NodeId| -> Vec<NodeId> {
+            self[node]
+                .input_ids()
+                .flat_map(|input| self.input_connections(input))
+                .map(|output| self[*output].node)
+                .filter(|dep| !cache.is_evaluated(*dep))
+                .collect()
+        };
+
+        // Iterative depth-first search, emitting nodes in post-order.
+        let mut order = Vec::new();
+        let mut done = HashSet::new();
+        let mut in_progress = HashSet::from([node_id]);
+        let mut stack = vec![(node_id, dependencies(node_id))];
+        while let Some((node, pending)) = stack.last_mut() {
+            if let Some(dep) = pending.pop() {
+                if done.contains(&dep) {
+                    continue;
+                }
+                if !in_progress.insert(dep) {
+                    let cycle = self
+                        .find_cycle()
+                        .expect("A node depending on itself should be part of a cycle");
+                    return Err(EvaluationError::Cycle(cycle));
+                }
+                stack.push((dep, dependencies(dep)));
+            } else {
+                let node = *node;
+                in_progress.remove(&node);
+                done.insert(node);
+                order.push(node);
+                stack.pop();
+            }
+        }
+        Ok(order)
+    }
+
+    /// Gathers the input values of `node_id`. All the nodes connected to its
+    /// inputs must have been evaluated.
+    fn node_inputs(
+        &self,
+        node_id: NodeId,
+        fed_inputs: &HashMap<InputId, Vec<NodeData::Value>>,
+        cache: &OutputsCache<NodeData::Value>,
+    ) -> Result<NodeInputs<NodeData::Value>, EvaluationError> {
+        let mut values = Vec::with_capacity(self[node_id].inputs.len());
+        for (name, input) in &self[node_id].inputs {
+            let connections = self.input_connections(*input);
+            let input_values = if !connections.is_empty() {
+                connections
+                    .iter()
+                    .map(|output| self.cached_output(*output, cache).cloned())
+                    .collect::<Result<Vec<_>, _>>()?
+            } else if let Some(fed_values) = fed_inputs.get(input) {
+                fed_values.clone()
+            } else {
+                match self[*input].kind {
+                    InputParamKind::ConnectionOnly => vec![],
+                    InputParamKind::ConstantOnly | InputParamKind::ConnectionOrConstant => {
+                        vec![self[*input].value.clone().into()]
+                    }
+                }
+            };
+            values.push((name.clone(), input_values));
+        }
+        Ok(NodeInputs { values })
+    }
+
+    /// Computes the outputs of a group node, by evaluating its nested graph
+    /// with the values of the inputs of the group node. Outputs forwarding a
+    /// removed nested output produce no value.
+    fn group_outputs(
+        &self,
+        node_id: NodeId,
+        group: &NodeGroup<NodeData, DataType, ValueType>,
+        inputs: &NodeInputs<NodeData::Value>,
+        state: &mut NodeData::EvalState,
+    ) -> Result<NodeOutputs<NodeData::Value>, NodeEvalError> {
+        let inner = &group.graph;
+        // Several group inputs may feed the same nested input, when it
+        // accepts multiple connections.
+        let mut fed_inputs: HashMap<InputId, Vec<NodeData::Value>> = HashMap::new();
+        for (name, input) in &self[node_id].inputs {
+            let values = inputs.get_all(name)?;
+            for inner_input in group.inputs.get(*input).into_iter().flatten() {
+                fed_inputs
+                    .entry(*inner_input)
+                    .or_default()
+                    .extend(values.iter().cloned());
+            }
+        }
+
+        // The nested graph is cached for this evaluation only, since its ids
+        // would clash with the ids of this graph.
+        let mut cache = OutputsCache::default();
+        let mut outputs = NodeOutputs::new();
+        for (name, output) in &self[node_id].outputs {
+            let inner_output = match group.outputs.get(*output) {
+                Some(inner_output) if inner.outputs.contains_key(*inner_output) => *inner_output,
+                _ => continue,
+            };
+            inner
+                .evaluate_node_fed(inner[inner_output].node, &fed_inputs, &mut cache, state)
+                .map_err(|err| NodeEvalError::InGroup(Box::new(err)))?;
+            if let Some(value) = cache.get(inner_output) {
+                outputs.set(name.clone(), value.clone());
+            }
+        }
+        Ok(outputs)
+    }
+
+    /// Computes the outputs of a bypassed node, by forwarding its inputs.
+    fn pass_through_outputs(
+        &self,
+        node_id: NodeId,
+        inputs: &NodeInputs<NodeData::Value>,
+    ) -> Result<NodeOutputs<NodeData::Value>, NodeEvalError> {
+        let mut outputs = NodeOutputs::new();
+        for (input, output) in self[node_id].user_data.pass_through(node_id, self) {
+            if let Some(value) = inputs.get_all(&input)?.first() {
+                outputs.set(output, value.clone());
+            }
+        }
+        Ok(outputs)
+    }
+
+    /// Returns the cached value of an output, or an error naming the output
+    /// if its node didn't produce it.
+    fn cached_output<'a>(
+        &self,
+        output: OutputId,
+        cache: &'a OutputsCache<NodeData::Value>,
+    ) -> Result<&'a NodeData::Value, EvaluationError> {
+        cache.get(output).ok_or_else(|| {
+            let node = self[output].node;
+            let name = self[node]
+                .outputs
+                .iter()
+                .find(|(_, id)| *id == output)
+                .map(|(name, _)| name.clone())
+                .unwrap_or_default();
+            EvaluationError::Node {
+                node,
+                label: self[node].label.clone(),
+                error: NodeEvalError::MissingOutput(name),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Adds up all its inputs, or fails when `fail` is set.
+    struct Sum {
+        fail: bool,
+    }
+
+    impl NodeEvaluatorTrait for Sum {
+        type DataType = ();
+        type ValueType = f32;
+        type Value = f32;
+        /// Counts the evaluated nodes.
+        type EvalState = usize;
+
+        fn evaluate(
+            &self,
+            _node_id: NodeId,
+            _graph: &Graph<Self, (), f32>,
+            inputs: &NodeInputs<f32>,
+            state: &mut usize,
+        ) -> Result<NodeOutputs<f32>, NodeEvalError> {
+            *state += 1;
+            if self.fail {
+                return Err(NodeEvalError::custom("failed").with_param("in"));
+            }
+            let sum = inputs.values.iter().flat_map(|(_, v)| v).sum::<f32>();
+            Ok(NodeOutputs::new().with("out", sum))
+        }
+
+        fn pass_through(
+            &self,
+            _node_id: NodeId,
+            _graph: &Graph<Self, (), f32>,
+        ) -> Vec<(String, String)> {
+            vec![("in".into(), "out".into())]
+        }
+    }
+
+    fn add_node(graph: &mut Graph<Sum, (), f32>, fail: bool, value: f32) -> NodeId {
+        graph.add_node("sum".into(), Sum { fail }, |graph, node_id| {
+            graph.add_multi_input_param(
+                node_id,
+                "in".into(),
+                (),
+                value,
+                InputParamKind::ConnectionOrConstant,
+                true,
+            );
+            graph.add_output_param(node_id, "out".into(), ());
+        })
+    }
+
+    fn connect(graph: &mut Graph<Sum, (), f32>, from: NodeId, to: NodeId) {
+        let output = graph[from].get_output("out").unwrap();
+        let input = graph[to].get_input("in").unwrap();
+        graph.add_connection(output, input);
+    }
+
+    #[test]
+    pub fn test_evaluation() {
+        let mut graph = Graph::new();
+        let a = add_node(&mut graph, false, 1.0);
+        let b = add_node(&mut graph, false, 2.0);
+        let c = add_node(&mut graph, false, 0.0);
+        connect(&mut graph, a, b);
+        connect(&mut graph, a, c);
+        connect(&mut graph, b, c);
+
+        let mut cache = OutputsCache::default();
+        let mut evaluated = 0;
+        let out = graph[c].get_output("out").unwrap();
+        let value = graph.evaluate_output(out, &mut cache, &mut evaluated);
+        assert_eq!(value.unwrap(), 2.0);
+        assert_eq!(evaluated, 3);
+
+        // Everything is cached now.
+        graph.evaluate_node(c, &mut cache, &mut evaluated).unwrap();
+        assert_eq!(evaluated, 3);
+    }
+
+    #[test]
+    pub fn test_incremental_evaluation() {
+        let mut graph = Graph::new();
+        let a = add_node(&mut graph, false, 1.0);
+        let b = add_node(&mut graph, false, 2.0);
+        let c = add_node(&mut graph, false, 0.0);
+        connect(&mut graph, a, c);
+        connect(&mut graph, b, c);
+
+        let mut cache = OutputsCache::default();
+        let mut evaluated = 0;
+        let out = graph[c].get_output("out").unwrap();
+        assert_eq!(graph.take_dirty_nodes().len(), 3);
+        graph.evaluate_node(c, &mut cache, &mut evaluated).unwrap();
+        assert_eq!(evaluated, 3);
+
+        // Changing a value only re-evaluates the nodes downstream.
+        let input = graph[b].get_input("in").unwrap();
+        graph[input].value = 5.0;
+        graph.mark_dirty(b);
+        let dirty = graph.take_dirty_nodes();
+        assert_eq!(dirty, HashSet::from([b, c]));
+        cache.invalidate(&graph, &dirty);
+        let value = graph.evaluate_output(out, &mut cache, &mut evaluated);
+        assert_eq!(value.unwrap(), 6.0);
+        assert_eq!(evaluated, 5);
+
+        // Removing a node marks its successors as dirty.
+        graph.remove_node(a);
+        assert_eq!(graph.take_dirty_nodes(), HashSet::from([a, c]));
+    }
+
+    #[test]
+    pub fn test_bypass() {
+        let mut graph = Graph::new();
+        let a = add_node(&mut graph, false, 1.0);
+        let b = add_node(&mut graph, false, 2.0);
+        let c = add_node(&mut graph, true, 0.0);
+        connect(&mut graph, a, c);
+        connect(&mut graph, b, c);
+
+        // The failing node forwards its first input instead of evaluating
+        graph.set_bypassed(c, true);
+        let out = graph[c].get_output("out").unwrap();
+        let mut evaluated = 0;
+        let value = graph.evaluate_output(out, &mut OutputsCache::default(), &mut evaluated);
+        assert_eq!(value.unwrap(), 1.0);
+        assert_eq!(evaluated, 2);
+
+        graph.take_dirty_nodes();
+        graph.set_bypassed(c, false);
+        assert_eq!(graph.take_dirty_nodes(), HashSet::from([c]));
+        let value = graph.evaluate_output(out, &mut OutputsCache::default(), &mut evaluated);
+        assert!(value.is_err());
+    }
+
+    #[test]
+    pub fn test_group_evaluation() {
+        let mut graph = Graph::new();
+        let a = add_node(&mut graph, false, 1.0);
+        let b = add_node(&mut graph, false, 2.0);
+        let c = add_node(&mut graph, false, 0.0);
+        let d = add_node(&mut graph, false, 0.0);
+        let e = add_node(&mut graph, false, 0.0);
+        connect(&mut graph, a, c);
+        connect(&mut graph, b, c);
+        connect(&mut graph, c, d);
+        connect(&mut graph, d, e);
+
+        // Both outer nodes feed the same nested input through their own
+        // group input, and the group forwards the output of the nested d.
+        let (group, inner_ids) = graph.group_nodes(&[c, d], "group".into(), Sum { fail: false });
+        assert_eq!(graph[group].inputs.len(), 2);
+        let out = graph[e].get_output("out").unwrap();
+        let mut evaluated = 0;
+        let value = graph.evaluate_output(out, &mut OutputsCache::default(), &mut evaluated);
+        assert_eq!(value.unwrap(), 3.0);
+        assert_eq!(evaluated, 5);
+
+        // Errors inside the group are reported on the group node
+        let inner_d = inner_ids[d];
+        graph.groups[group].graph[inner_d].user_data.fail = true;
+        let err = graph
+            .evaluate_node(e, &mut OutputsCache::default(), &mut 0)
+            .unwrap_err();
+        assert_eq!(err.node(), Some(group));
+        match err {
+            EvaluationError::Node {
+                error: NodeEvalError::InGroup(inner),
+                ..
+            } => assert_eq!(inner.node(), Some(inner_d)),
+            _ => panic!("Unexpected error {err}"),
+        }
+    }
+
+    #[test]
+    pub fn test_evaluation_errors() {
+        let mut graph = Graph::new();
+        let a = add_node(&mut graph, true, 0.0);
+        let b = add_node(&mut graph, false, 0.0);
+        connect(&mut graph, a, b);
+
+        let err = graph
+            .evaluate_node(b, &mut OutputsCache::default(), &mut 0)
+            .unwrap_err();
+        assert_eq!(err.node(), Some(a));
+        assert_eq!(err.param(), Some("in"));
+
+        connect(&mut graph, b, a);
+        let err = graph
+            .evaluate_node(b, &mut OutputsCache::default(), &mut 0)
+            .unwrap_err();
+        assert!(matches!(err, EvaluationError::Cycle(_)));
+    }
+}
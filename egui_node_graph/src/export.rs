@@ -0,0 +1,399 @@
+use super::*;
+use slotmap::Key;
+use std::fmt::Write;
+
+/// Formats a color as `#rrggbb`, ignoring its alpha.
+fn hex_color(color: egui::Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+/// Escapes the characters with a special meaning in DOT record labels.
+fn escape_record(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '{' | '}' | '|' | '<' | '>' | '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes the characters with a special meaning in quoted Mermaid text.
+fn escape_mermaid(text: &str) -> String {
+    text.replace('"', "#quot;").replace('\n', "<br>")
+}
+
+/// The name of a node in the exported graphs, given its id in the interchange
+/// format, see [`GraphFile`]. The nodes of nested graphs are prefixed with the
+/// name of their group node.
+fn node_name(prefix: &str, file_id: u32) -> String {
+    format!("{prefix}n{file_id}")
+}
+
+/// The names of the ports of DOT records.
+fn input_port(input: InputId) -> String {
+    format!("i{}", input.data().as_ffi() as u32)
+}
+
+fn output_port(output: OutputId) -> String {
+    format!("o{}", output.data().as_ffi() as u32)
+}
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
+    /// The inputs of `node_id` that have a port, with their name.
+    fn port_inputs(&self, node_id: NodeId) -> impl Iterator<Item = (&str, InputId)> + '_ {
+        self[node_id]
+            .inputs
+            .iter()
+            .filter(|(_, input)| !matches!(self[*input].kind, InputParamKind::ConstantOnly))
+            .map(|(name, input)| (name.as_str(), *input))
+    }
+
+    /// Exports the graph as a Graphviz DOT digraph, for documentation or
+    /// reviews. Nodes are drawn as records, with a field for their label and
+    /// one for each port, and edges are colored after the type of their
+    /// output, see [`DataTypeTrait::data_type_color`]. The contents of group
+    /// nodes are drawn in a cluster next to them, linked to the ports of the
+    /// group node by dashed edges.
+    ///
+    /// When `node_positions` are given, nodes are pinned to their position in
+    /// the editor. Only the layout engines reading positions, like `neato -n`,
+    /// honor them: `dot` lays out the graph on its own.
+    pub fn to_dot<UserState>(
+        &self,
+        node_positions: Option<&SecondaryMap<NodeId, egui::Pos2>>,
+        user_state: &mut UserState,
+    ) -> String
+    where
+        DataType: DataTypeTrait<UserState>,
+    {
+        let mut dot = String::from("digraph {\n    rankdir=LR;\n    node [shape=record];\n");
+        self.write_dot("", "    ", node_positions, user_state, &mut dot);
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Writes the nodes and edges of the graph, and of its groups, to a DOT
+    /// graph. Node names start with `prefix`, and lines with `indent`.
+    fn write_dot<UserState>(
+        &self,
+        prefix: &str,
+        indent: &str,
+        node_positions: Option<&SecondaryMap<NodeId, egui::Pos2>>,
+        user_state: &mut UserState,
+        dot: &mut String,
+    ) where
+        DataType: DataTypeTrait<UserState>,
+    {
+        let file_ids = self.file_ids(&SecondaryMap::new());
+        let name = |node: NodeId| node_name(prefix, file_ids[node]);
+        for node_id in self.iter_nodes() {
+            let node = &self[node_id];
+            let mut fields = Vec::new();
+            let inputs: Vec<String> = self
+                .port_inputs(node_id)
+                .map(|(name, input)| format!("<{}> {}", input_port(input), escape_record(name)))
+                .collect();
+            if !inputs.is_empty() {
+                fields.push(format!("{{{}}}", inputs.join("|")));
+            }
+            fields.push(escape_record(&node.label));
+            let outputs: Vec<String> = node
+                .outputs
+                .iter()
+                .map(|(name, output)| format!("<{}> {}", output_port(*output), escape_record(name)))
+                .collect();
+            if !outputs.is_empty() {
+                fields.push(format!("{{{}}}", outputs.join("|")));
+            }
+
+            // The outer braces lay the fields out from left to right, since
+            // records are flipped by `rankdir=LR`. The ports are flipped back
+            // into columns.
+            let _ = write!(
+                dot,
+                "{indent}{} [label=\"{{{}}}\"",
+                name(node_id),
+                fields.join("|")
+            );
+            if let Some(pos) = node_positions.and_then(|positions| positions.get(node_id)) {
+                // DOT's y axis points up, unlike egui's
+                let _ = write!(dot, ", pos=\"{},{}!\"", pos.x, -pos.y);
+            }
+            dot.push_str("];\n");
+        }
+
+        for node_id in self.iter_nodes() {
+            for (_, input) in self.port_inputs(node_id) {
+                for output in self.input_connections(input).iter().copied() {
+                    let color = self[output].typ.data_type_color(user_state);
+                    let _ = writeln!(
+                        dot,
+                        "{indent}{}:{} -> {}:{} [color=\"{}\"];",
+                        name(self[output].node),
+                        output_port(output),
+                        name(node_id),
+                        input_port(input),
+                        hex_color(color),
+                    );
+                }
+            }
+        }
+
+        for (node_id, group) in self.groups.iter() {
+            let group_name = name(node_id);
+            let inner = &group.graph;
+            let inner_ids = inner.file_ids(&group.file_ids);
+            let inner_prefix = format!("{group_name}_");
+            let inner_name = |node: NodeId| node_name(&inner_prefix, inner_ids[node]);
+            let _ = writeln!(
+                dot,
+                "{indent}subgraph cluster_{group_name} {{\n{indent}    label=\"{}\";\n{indent}    style=dashed;",
+                escape_record(&self[node_id].label)
+            );
+            inner.write_dot(
+                &inner_prefix,
+                &format!("{indent}    "),
+                None,
+                user_state,
+                dot,
+            );
+            let _ = writeln!(dot, "{indent}}}");
+
+            for (input, inner_inputs) in group.inputs.iter() {
+                for inner_input in inner_inputs {
+                    if let Some(param) = inner.try_get_input(*inner_input) {
+                        let _ = writeln!(
+                            dot,
+                            "{indent}{group_name}:{} -> {}:{} [style=dashed];",
+                            input_port(input),
+                            inner_name(param.node),
+                            input_port(*inner_input),
+                        );
+                    }
+                }
+            }
+            for (output, inner_output) in group.outputs.iter() {
+                if let Some(param) = inner.try_get_output(*inner_output) {
+                    let _ = writeln!(
+                        dot,
+                        "{indent}{}:{} -> {group_name}:{} [style=dashed];",
+                        inner_name(param.node),
+                        output_port(*inner_output),
+                        output_port(output),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Exports the graph as a Mermaid flowchart, which can be embedded in
+    /// markdown documents. Mermaid has no ports, so each edge is labeled with
+    /// the names of the output and input it connects, and colored after the
+    /// type of the output, see [`DataTypeTrait::data_type_color`]. The
+    /// contents of group nodes are drawn in a subgraph next to them.
+    ///
+    /// Mermaid always computes its own layout, so node positions are not
+    /// exported.
+    pub fn to_mermaid<UserState>(&self, user_state: &mut UserState) -> String
+    where
+        DataType: DataTypeTrait<UserState>,
+    {
+        let mut mermaid = String::from("flowchart LR\n");
+        let mut colors = Vec::new();
+        self.write_mermaid("", "    ", user_state, &mut mermaid, &mut colors);
+        // Links are styled by their index, in order of declaration
+        for (index, color) in colors.into_iter().enumerate() {
+            if let Some(color) = color {
+                let _ = writeln!(mermaid, "    linkStyle {index} stroke:{}", hex_color(color));
+            }
+        }
+        mermaid
+    }
+
+    /// Writes the nodes and links of the graph, and of its groups, to a
+    /// Mermaid flowchart, and the color of each link to `colors`. Links
+    /// between group nodes and their contents have no color.
+    fn write_mermaid<UserState>(
+        &self,
+        prefix: &str,
+        indent: &str,
+        user_state: &mut UserState,
+        mermaid: &mut String,
+        colors: &mut Vec<Option<egui::Color32>>,
+    ) where
+        DataType: DataTypeTrait<UserState>,
+    {
+        let file_ids = self.file_ids(&SecondaryMap::new());
+        let name = |node: NodeId| node_name(prefix, file_ids[node]);
+        for node_id in self.iter_nodes() {
+            let _ = writeln!(
+                mermaid,
+                "{indent}{}[\"{}\"]",
+                name(node_id),
+                escape_mermaid(&self[node_id].label)
+            );
+        }
+
+        for node_id in self.iter_nodes() {
+            for (input_name, input) in self.port_inputs(node_id) {
+                for output in self.input_connections(input).iter().copied() {
+                    let source = self[output].node;
+                    let _ = writeln!(
+                        mermaid,
+                        "{indent}{} -- \"{} → {}\" --> {}",
+                        name(source),
+                        escape_mermaid(self.output_name(output)),
+                        escape_mermaid(input_name),
+                        name(node_id),
+                    );
+                    colors.push(Some(self[output].typ.data_type_color(user_state)));
+                }
+            }
+        }
+
+        for (node_id, group) in self.groups.iter() {
+            let group_name = name(node_id);
+            let inner = &group.graph;
+            let inner_ids = inner.file_ids(&group.file_ids);
+            let inner_prefix = format!("{group_name}_");
+            let inner_name = |node: NodeId| node_name(&inner_prefix, inner_ids[node]);
+            let _ = writeln!(
+                mermaid,
+                "{indent}subgraph {group_name}_group [\"{}\"]",
+                escape_mermaid(&self[node_id].label)
+            );
+            inner.write_mermaid(
+                &inner_prefix,
+                &format!("{indent}    "),
+                user_state,
+                mermaid,
+                colors,
+            );
+            let _ = writeln!(mermaid, "{indent}end");
+
+            for inner_inputs in group.inputs.values() {
+                for inner_input in inner_inputs {
+                    if let Some(param) = inner.try_get_input(*inner_input) {
+                        let _ = writeln!(
+                            mermaid,
+                            "{indent}{group_name} -.-> {}",
+                            inner_name(param.node)
+                        );
+                        colors.push(None);
+                    }
+                }
+            }
+            for inner_output in group.outputs.values() {
+                if let Some(param) = inner.try_get_output(*inner_output) {
+                    let _ = writeln!(
+                        mermaid,
+                        "{indent}{} -.-> {group_name}",
+                        inner_name(param.node)
+                    );
+                    colors.push(None);
+                }
+            }
+        }
+    }
+
+    /// The name of `output` in its node.
+    fn output_name(&self, output: OutputId) -> &str {
+        self[self[output].node]
+            .outputs
+            .iter()
+            .find(|(_, id)| *id == output)
+            .map_or("", |(name, _)| name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    pub fn test_export() {
+        let mut graph = TestGraph::new();
+        let a = graph.add_node("A".into(), (), |graph, node_id| {
+            graph.add_output_param(node_id, "out".into(), Type::Float);
+        });
+        let b = graph.add_node("\"B\"".into(), (), |graph, node_id| {
+            graph.add_input_param(
+                node_id,
+                "in".into(),
+                Type::Float,
+                (),
+                InputParamKind::ConnectionOnly,
+                true,
+            );
+        });
+        let output = graph[a].get_output("out").unwrap();
+        let input = graph[b].get_input("in").unwrap();
+        graph.add_connection(output, input);
+        let (a, b) = (node_name("", 0), node_name("", 1));
+        let (output, input) = (output_port(output), input_port(input));
+
+        let mut positions = SecondaryMap::new();
+        positions.insert(graph.iter_nodes().next().unwrap(), egui::pos2(10.0, 20.0));
+        let dot = graph.to_dot(Some(&positions), &mut ());
+        assert!(dot.contains(&format!(
+            "{a} [label=\"{{A|{{<{output}> out}}}}\", pos=\"10,-20!\"];"
+        )));
+        assert!(dot.contains(&format!("{b} [label=\"{{{{<{input}> in}}|\\\"B\\\"}}\"];")));
+        assert!(dot.contains(&format!("{a}:{output} -> {b}:{input} [color=\"#ff8000\"];")));
+
+        let mermaid = graph.to_mermaid(&mut ());
+        assert!(mermaid.contains(&format!("{b}[\"#quot;B#quot;\"]")));
+        assert!(mermaid.contains(&format!("{a} -- \"out → in\" --> {b}")));
+        assert!(mermaid.contains("linkStyle 0 stroke:#ff8000"));
+    }
+
+    #[test]
+    pub fn test_export_group() {
+        let mut graph = TestGraph::new();
+        let a = graph.add_node("A".into(), (), |graph, node_id| {
+            graph.add_output_param(node_id, "out".into(), Type::Float);
+        });
+        let b = graph.add_node("B".into(), (), |graph, node_id| {
+            graph.add_input_param(
+                node_id,
+                "in".into(),
+                Type::Float,
+                (),
+                InputParamKind::ConnectionOnly,
+                true,
+            );
+        });
+        let output = graph[a].get_output("out").unwrap();
+        let input = graph[b].get_input("in").unwrap();
+        graph.add_connection(output, input);
+        let (group, inner_ids) = graph.group_nodes(&[b], "G".into(), ());
+        let group_input = graph[group].get_input("in").unwrap();
+        let inner_input = graph.groups[group].graph[inner_ids[b]]
+            .get_input("in")
+            .unwrap();
+        let (group_port, inner_port) = (input_port(group_input), input_port(inner_input));
+
+        // The group node is n1, and the B node inside of it is n1_n0
+        let dot = graph.to_dot(None, &mut ());
+        assert!(dot.contains("    subgraph cluster_n1 {\n        label=\"G\";"));
+        assert!(dot.contains(&format!(
+            "        n1_n0 [label=\"{{{{<{inner_port}> in}}|B}}\"];"
+        )));
+        assert!(dot.contains(&format!(
+            "    n1:{group_port} -> n1_n0:{inner_port} [style=dashed];"
+        )));
+
+        let mermaid = graph.to_mermaid(&mut ());
+        assert!(mermaid.contains("    subgraph n1_group [\"G\"]\n        n1_n0[\"B\"]\n    end"));
+        assert!(mermaid.contains("    n1 -.-> n1_n0"));
+        assert!(mermaid.contains("linkStyle 0 stroke:#ff8000"));
+        assert!(!mermaid.contains("linkStyle 1"));
+    }
+}
@@ -0,0 +1,323 @@
+use super::*;
+use egui::{Align2, Color32, CursorIcon, Id, Pos2, Rect, Rounding, Sense, Stroke, TextEdit, Vec2};
+
+#[cfg(feature = "persistence")]
+use serde::{Deserialize, Serialize};
+
+/// The height of the title bar of a frame, which is used to drag it.
+const FRAME_TITLE_HEIGHT: f32 = 24.0;
+/// The size of the handle at the bottom right corner of a frame.
+const FRAME_HANDLE_SIZE: f32 = 12.0;
+/// Frames can't be resized below this size.
+const FRAME_MIN_SIZE: Vec2 = Vec2 { x: 120.0, y: 60.0 };
+
+/// A titled, colored frame drawn behind the nodes of the editor, used to
+/// document a region of the graph. Frames don't belong to the [`Graph`], they
+/// are stored in the [`GraphEditorState`] next to the node positions.
+///
+/// Dragging the title bar of a frame moves the nodes inside it too, see
+/// [`GraphEditorState::frame_nodes`]. Frames are resized from their bottom
+/// right corner, renamed by double clicking their title, and their context
+/// menu allows changing their color or deleting them.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct CommentFrame {
+    pub title: String,
+    /// The position of the top left corner of the frame, in the same
+    /// coordinates as the node positions.
+    pub position: Pos2,
+    pub size: Vec2,
+    pub color: Color32,
+}
+
+impl CommentFrame {
+    pub fn new(title: impl Into<String>, position: Pos2, size: Vec2) -> Self {
+        Self {
+            title: title.into(),
+            position,
+            size,
+            color: Color32::from_rgb(0x4a, 0x6f, 0xa5),
+        }
+    }
+
+    /// Builder-style setter for the color of the frame.
+    pub fn with_color(mut self, color: Color32) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn rect(&self) -> Rect {
+        Rect::from_min_size(self.position, self.size)
+    }
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+{
+    /// Adds a frame to the open graph, on top of its other frames. Returns
+    /// the id of the frame.
+    pub fn add_frame(&mut self, frame: CommentFrame) -> FrameId {
+        self.with_open_group(|state| {
+            let frame_id = state.frames.insert(frame);
+            state.frame_order.push(frame_id);
+            frame_id
+        })
+    }
+
+    /// Removes a frame of the open graph. The nodes inside it are kept.
+    pub fn remove_frame(&mut self, frame_id: FrameId) -> Option<CommentFrame> {
+        self.with_open_group(|state| state.remove_frame_in_open_graph(frame_id))
+    }
+
+    /// Returns the nodes of the open graph inside a frame: The nodes whose
+    /// top left corner lies inside the frame, in draw order. These are the
+    /// nodes that move along with the frame.
+    pub fn frame_nodes(&mut self, frame_id: FrameId) -> Vec<NodeId> {
+        self.with_open_group(|state| state.frame_nodes_in_open_graph(frame_id))
+    }
+
+    /// Like [`GraphEditorState::remove_frame`], for a state that has the open
+    /// group swapped in.
+    pub(crate) fn remove_frame_in_open_graph(&mut self, frame_id: FrameId) -> Option<CommentFrame> {
+        self.frame_order.retain(|id| *id != frame_id);
+        self.frames.remove(frame_id)
+    }
+
+    /// Like [`GraphEditorState::frame_nodes`], for a state that has the open
+    /// group swapped in.
+    pub(crate) fn frame_nodes_in_open_graph(&self, frame_id: FrameId) -> Vec<NodeId> {
+        let Some(frame) = self.frames.get(frame_id) else {
+            return Vec::new();
+        };
+        let rect = frame.rect();
+        self.node_order
+            .iter()
+            .copied()
+            .filter(|node| {
+                self.node_positions
+                    .get(*node)
+                    .is_some_and(|pos| rect.contains(*pos))
+            })
+            .collect()
+    }
+
+    /// Moves a frame of the open graph, together with the given nodes, and
+    /// raises it above the other frames.
+    pub(crate) fn move_frame_in_open_graph(
+        &mut self,
+        frame_id: FrameId,
+        nodes: &[NodeId],
+        drag_delta: Vec2,
+    ) {
+        let Some(frame) = self.frames.get_mut(frame_id) else {
+            return;
+        };
+        frame.position += drag_delta;
+        for node in nodes {
+            if let Some(pos) = self.node_positions.get_mut(*node) {
+                *pos += drag_delta;
+            }
+        }
+        self.frame_order.retain(|id| *id != frame_id);
+        self.frame_order.push(frame_id);
+    }
+
+    /// Draws the frames of the open graph, which must be swapped in. Frames
+    /// are drawn in `frame_order`, and must be drawn before the nodes so they
+    /// stay behind them.
+    pub(crate) fn show_frames<UserResponse>(
+        &mut self,
+        ui: &mut egui::Ui,
+        pan: Vec2,
+    ) -> Vec<NodeResponse<UserResponse, NodeData>>
+    where
+        UserResponse: UserResponseTrait,
+        NodeData: NodeDataTrait,
+    {
+        let mut responses = Vec::new();
+        let frame_ids: Vec<FrameId> = self
+            .frame_order
+            .iter()
+            .copied()
+            .filter(|id| self.frames.contains_key(*id))
+            .collect();
+
+        for frame_id in frame_ids {
+            let rect = self.frames[frame_id].rect().translate(pan);
+            let title_rect = Rect::from_min_size(
+                rect.min,
+                egui::vec2(rect.width(), FRAME_TITLE_HEIGHT.min(rect.height())),
+            );
+            let color = self.frames[frame_id].color;
+            let rounding = 4.0;
+            ui.painter()
+                .rect_filled(rect, rounding, color.linear_multiply(0.15));
+            ui.painter().rect_filled(
+                title_rect,
+                Rounding {
+                    nw: rounding,
+                    ne: rounding,
+                    sw: 0.0,
+                    se: 0.0,
+                },
+                color.linear_multiply(0.6),
+            );
+
+            let title_resp = ui.interact(
+                title_rect,
+                Id::new((frame_id, "frame_title")),
+                Sense::click_and_drag(),
+            );
+
+            // Dragging the title bar moves the frame, and the nodes that were
+            // inside it when the drag started.
+            let nodes_id = Id::new((frame_id, "frame_nodes"));
+            if title_resp.drag_started() {
+                let nodes = self.frame_nodes_in_open_graph(frame_id);
+                ui.ctx()
+                    .memory_mut(|mem| mem.data.insert_temp(nodes_id, nodes));
+            }
+            let drag_delta = title_resp.drag_delta();
+            if drag_delta.length_sq() > 0.0 {
+                let nodes = ui
+                    .ctx()
+                    .memory_mut(|mem| mem.data.get_temp::<Vec<NodeId>>(nodes_id))
+                    .unwrap_or_default();
+                responses.push(NodeResponse::MoveFrame {
+                    frame: frame_id,
+                    nodes,
+                    drag_delta,
+                });
+            }
+
+            // The title is edited in place after double clicking it.
+            let frame = &mut self.frames[frame_id];
+            let frame_before = frame.clone();
+            let editing_id = Id::new((frame_id, "frame_editing"));
+            let text_edit_id = Id::new((frame_id, "frame_title_edit"));
+            if title_resp.double_clicked() {
+                ui.ctx().memory_mut(|mem| {
+                    mem.data.insert_temp(editing_id, true);
+                    mem.request_focus(text_edit_id);
+                });
+            }
+            let editing = ui
+                .ctx()
+                .memory_mut(|mem| mem.data.get_temp::<bool>(editing_id))
+                .unwrap_or(false);
+            if editing {
+                let edit = ui.put(
+                    title_rect.shrink2(egui::vec2(8.0, 2.0)),
+                    TextEdit::singleline(&mut frame.title)
+                        .id(text_edit_id)
+                        .frame(false),
+                );
+                if edit.lost_focus() {
+                    ui.ctx()
+                        .memory_mut(|mem| mem.data.remove::<bool>(editing_id));
+                }
+            } else {
+                ui.painter().text(
+                    title_rect.left_center() + egui::vec2(8.0, 0.0),
+                    Align2::LEFT_CENTER,
+                    &frame.title,
+                    egui::TextStyle::Button.resolve(ui.style()),
+                    Color32::WHITE,
+                );
+            }
+
+            title_resp.context_menu(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Color");
+                    ui.color_edit_button_srgba(&mut frame.color);
+                });
+                if ui.button("Delete frame").clicked() {
+                    responses.push(NodeResponse::DeleteFrame(frame_id));
+                    ui.close_menu();
+                }
+            });
+            if *frame != frame_before {
+                responses.push(NodeResponse::EditFrame(frame_id));
+            }
+
+            // The handle at the bottom right corner resizes the frame
+            let handle_rect = Rect::from_min_size(
+                rect.max - Vec2::splat(FRAME_HANDLE_SIZE),
+                Vec2::splat(FRAME_HANDLE_SIZE),
+            );
+            let handle_resp = ui.interact(
+                handle_rect,
+                Id::new((frame_id, "frame_resize")),
+                Sense::drag(),
+            );
+            if handle_resp.hovered() || handle_resp.dragged() {
+                ui.ctx().set_cursor_icon(CursorIcon::ResizeNwSe);
+            }
+            let stroke = Stroke::new(1.5, color);
+            for offset in [4.0, 8.0] {
+                ui.painter().line_segment(
+                    [
+                        handle_rect.max - egui::vec2(offset, 2.0),
+                        handle_rect.max - egui::vec2(2.0, offset),
+                    ],
+                    stroke,
+                );
+            }
+            let size_delta = handle_resp.drag_delta();
+            if size_delta.length_sq() > 0.0 {
+                responses.push(NodeResponse::ResizeFrame {
+                    frame: frame_id,
+                    size: (frame.size + size_delta).max(FRAME_MIN_SIZE),
+                });
+            }
+        }
+        responses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestState = GraphEditorState<(), (), (), (), ()>;
+
+    fn add_node(state: &mut TestState, pos: Pos2) -> NodeId {
+        let node = state.graph.add_node("node".into(), (), |_, _| {});
+        state.node_order.push(node);
+        state.node_positions.insert(node, pos);
+        node
+    }
+
+    #[test]
+    pub fn test_frames() {
+        let mut state = TestState::default();
+        let inside = add_node(&mut state, egui::pos2(50.0, 50.0));
+        let outside = add_node(&mut state, egui::pos2(500.0, 50.0));
+        let a = state.add_frame(CommentFrame::new("a", Pos2::ZERO, egui::vec2(200.0, 200.0)));
+        let b = state.add_frame(CommentFrame::new(
+            "b",
+            egui::pos2(400.0, 0.0),
+            egui::vec2(50.0, 50.0),
+        ));
+        assert_eq!(state.frame_nodes(a), vec![inside]);
+
+        // Moving a frame moves its nodes, and raises it
+        let nodes = state.frame_nodes(a);
+        state.move_frame_in_open_graph(a, &nodes, egui::vec2(10.0, 0.0));
+        assert_eq!(state.frames[a].position, egui::pos2(10.0, 0.0));
+        assert_eq!(state.node_positions[inside], egui::pos2(60.0, 50.0));
+        assert_eq!(state.node_positions[outside], egui::pos2(500.0, 50.0));
+        assert_eq!(state.frame_order, vec![b, a]);
+
+        // Frames belong to the graph they were added to
+        let group = state.group_nodes(&[inside], "group".into(), ());
+        state.open_group(group);
+        state.add_frame(CommentFrame::new("c", Pos2::ZERO, Vec2::splat(100.0)));
+        state.close_groups_to(0);
+        assert_eq!(state.frames.len(), 2);
+        assert_eq!(state.graph.groups[group].frames.len(), 1);
+
+        state.remove_frame(b);
+        assert_eq!(state.frame_order, vec![a]);
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use std::collections::HashSet;
 
 #[cfg(feature = "persistence")]
 use serde::{Deserialize, Serialize};
@@ -14,11 +15,16 @@ pub struct Node<NodeData> {
     pub inputs: Vec<(String, InputId)>,
     pub outputs: Vec<(String, OutputId)>,
     pub user_data: NodeData,
+    /// Bypassed nodes are disabled without removing them from the graph: The
+    /// editor draws them dimmed, and evaluation forwards their inputs to their
+    /// outputs instead of evaluating them. See [`Graph::set_bypassed`].
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub bypassed: bool,
 }
 
 /// The three kinds of input params. These describe how the graph must behave
 /// with respect to inline widgets and connections for this parameter.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
 pub enum InputParamKind {
     /// No constant value can be set. Only incoming connections can produce it
@@ -30,6 +36,33 @@ pub enum InputParamKind {
     ConnectionOrConstant,
 }
 
+/// How many incoming connections an input parameter accepts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub enum InputCardinality {
+    /// A single connection. Connecting another output replaces the existing
+    /// connection.
+    #[default]
+    Single,
+    /// Any number of connections, kept in the order they were made.
+    Many,
+}
+
+/// Marks a parameter as a wildcard: A parameter of a generic type, which
+/// takes the type of the ports it gets connected to. See
+/// [`Graph::infer_wildcard_types`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct Wildcard<DataType> {
+    /// The type of the parameter while no connection determines it. This
+    /// type should accept connections from and to any other type, see
+    /// [`DataTypeTrait::can_connect_to`].
+    pub unresolved: DataType,
+    /// The wildcard parameters of a node with the same link always have the
+    /// same type.
+    pub link: usize,
+}
+
 #[cfg(feature = "persistence")]
 fn shown_inline_default() -> bool {
     true
@@ -58,6 +91,18 @@ pub struct InputParam<DataType, ValueType> {
     /// When true, the node is shown inline inside the node graph.
     #[cfg_attr(feature = "persistence", serde(default = "shown_inline_default"))]
     pub shown_inline: bool,
+    /// The number of connections this parameter accepts. See
+    /// [`InputCardinality`]
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub cardinality: InputCardinality,
+    /// Set for wildcard parameters, whose type is inferred from their
+    /// connections. See [`Wildcard`]
+    #[cfg_attr(feature = "persistence", serde(default = "Option::default"))]
+    pub wildcard: Option<Wildcard<DataType>>,
+    /// Set for the slots of a variadic input, to the base name of the
+    /// variadic input. See [`Graph::add_variadic_input_param`]
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub variadic: Option<String>,
 }
 
 /// An output parameter. Output parameters are inside a node, and represent the
@@ -71,6 +116,10 @@ pub struct OutputParam<DataType> {
     /// Back-reference to the node containing this parameter.
     pub node: NodeId,
     pub typ: DataType,
+    /// Set for wildcard parameters, whose type is inferred from their
+    /// connections. See [`Wildcard`]
+    #[cfg_attr(feature = "persistence", serde(default = "Option::default"))]
+    pub wildcard: Option<Wildcard<DataType>>,
 }
 
 /// The graph, containing nodes, input parameters and output parameters. Because
@@ -78,6 +127,10 @@ pub struct OutputParam<DataType> {
 /// crate to represent all the inner references in the data.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "persistence",
+    serde(from = "SerializedGraph<NodeData, DataType, ValueType>")
+)]
 pub struct Graph<NodeData, DataType, ValueType> {
     /// The [`Node`]s of the graph
     pub nodes: SlotMap<NodeId, Node<NodeData>>,
@@ -85,7 +138,129 @@ pub struct Graph<NodeData, DataType, ValueType> {
     pub inputs: SlotMap<InputId, InputParam<DataType, ValueType>>,
     /// The [`OutputParam`]s of the graph
     pub outputs: SlotMap<OutputId, OutputParam<DataType>>,
-    // Connects the input of a node, to the output of its predecessor that
-    // produces it
-    pub connections: SecondaryMap<InputId, OutputId>,
+    // Connects the input of a node, to the outputs of its predecessors that
+    // produce it. Inputs with [`InputCardinality::Single`] have at most one
+    // element, and inputs without connections have no entry.
+    //
+    // NOTE: Prefer the `Graph` methods to modify the connections, or call
+    // `rebuild_outgoing_connections` afterwards.
+    pub connections: SecondaryMap<InputId, Vec<OutputId>>,
+    /// The reverse of `connections`: The inputs fed by each output, in the
+    /// order the connections were made. Outputs without connections have no
+    /// entry. This is kept up to date by the methods modifying the graph, and
+    /// rebuilt when the graph is deserialized.
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub outgoing_connections: SecondaryMap<OutputId, Vec<InputId>>,
+    /// Nodes whose outputs may have changed since they were last evaluated.
+    /// See [`Graph::mark_dirty`]. This is not persisted: Loaded graphs start
+    /// with no dirty nodes.
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub dirty_nodes: HashSet<NodeId>,
+    /// The contents of the group nodes of this graph. See [`NodeGroup`].
+    pub groups: SecondaryMap<NodeId, NodeGroup<NodeData, DataType, ValueType>>,
+    /// Notified of the structural changes of the graph. See
+    /// [`Graph::add_observer`]. Observers are neither cloned nor persisted.
+    #[cfg_attr(feature = "persistence", serde(skip_serializing))]
+    pub observers: GraphObservers<NodeData, DataType, ValueType>,
+}
+
+/// The contents of a group node: A nested graph containing the nodes that
+/// were collapsed into the group, and the editor state needed to display it.
+///
+/// The ports of the group node stand for the connections that crossed the
+/// boundary of the group when it was created. Each input of the group node
+/// feeds some inputs of the nested graph, and each output of the group node
+/// forwards an output of the nested graph.
+///
+/// The ports are not updated when the nested graph is edited: Ports standing
+/// for removed nested parameters are ignored, and new nested parameters get no
+/// port. Use [`Graph::ungroup_node`] and group the nodes again to update them.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct NodeGroup<NodeData, DataType, ValueType> {
+    pub graph: Graph<NodeData, DataType, ValueType>,
+    /// The draw order of the nodes of the nested graph.
+    pub node_order: Vec<NodeId>,
+    /// The position of each node of the nested graph.
+    pub node_positions: SecondaryMap<NodeId, egui::Pos2>,
+    /// The comment frames of the nested graph.
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub frames: SlotMap<FrameId, CommentFrame>,
+    /// The draw order of the frames of the nested graph.
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub frame_order: Vec<FrameId>,
+    /// The knots of the wires of the nested graph.
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub knots: WireKnots,
+    /// The ids of the nodes of the nested graph in the interchange file it was
+    /// loaded from. See [`GraphEditorState::file_ids`].
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub file_ids: SecondaryMap<NodeId, u32>,
+    /// The inputs of the nested graph fed by each input of the group node.
+    /// These may refer to inputs removed since the group was created.
+    pub inputs: SecondaryMap<InputId, Vec<InputId>>,
+    /// The output of the nested graph forwarded by each output of the group
+    /// node. These may refer to outputs removed since the group was created.
+    pub outputs: SecondaryMap<OutputId, OutputId>,
+}
+
+/// The persisted fields of a [`Graph`]. The graph is deserialized through this
+/// struct so that its derived indices can be rebuilt.
+#[cfg(feature = "persistence")]
+#[derive(Deserialize)]
+struct SerializedGraph<NodeData, DataType, ValueType> {
+    nodes: SlotMap<NodeId, Node<NodeData>>,
+    inputs: SlotMap<InputId, InputParam<DataType, ValueType>>,
+    outputs: SlotMap<OutputId, OutputParam<DataType>>,
+    #[serde(deserialize_with = "deserialize_connections")]
+    connections: SecondaryMap<InputId, Vec<OutputId>>,
+    #[serde(default = "SecondaryMap::default")]
+    groups: SecondaryMap<NodeId, NodeGroup<NodeData, DataType, ValueType>>,
+}
+
+#[cfg(feature = "persistence")]
+impl<NodeData, DataType, ValueType> From<SerializedGraph<NodeData, DataType, ValueType>>
+    for Graph<NodeData, DataType, ValueType>
+{
+    fn from(serialized: SerializedGraph<NodeData, DataType, ValueType>) -> Self {
+        let mut graph = Self {
+            nodes: serialized.nodes,
+            inputs: serialized.inputs,
+            outputs: serialized.outputs,
+            connections: serialized.connections,
+            outgoing_connections: SecondaryMap::default(),
+            dirty_nodes: HashSet::default(),
+            groups: serialized.groups,
+            observers: GraphObservers::default(),
+        };
+        graph.rebuild_outgoing_connections();
+        graph
+    }
+}
+
+/// Deserializes the connections of a graph, also accepting graphs saved before
+/// inputs could have multiple connections, which stored a single output per
+/// input.
+#[cfg(feature = "persistence")]
+fn deserialize_connections<'de, D>(
+    deserializer: D,
+) -> Result<SecondaryMap<InputId, Vec<OutputId>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(OutputId),
+        Many(Vec<OutputId>),
+    }
+
+    let connections = SecondaryMap::<InputId, OneOrMany>::deserialize(deserializer)?;
+    Ok(connections
+        .into_iter()
+        .map(|(input, outputs)| match outputs {
+            OneOrMany::One(output) => (input, vec![output]),
+            OneOrMany::Many(outputs) => (input, outputs),
+        })
+        .collect())
 }
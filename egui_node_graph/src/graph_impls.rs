@@ -1,4 +1,5 @@
 use super::*;
+use std::collections::HashSet;
 
 impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
     pub fn new() -> Self {
@@ -7,6 +8,10 @@ impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
             inputs: SlotMap::default(),
             outputs: SlotMap::default(),
             connections: SecondaryMap::default(),
+            outgoing_connections: SecondaryMap::default(),
+            dirty_nodes: HashSet::default(),
+            groups: SecondaryMap::default(),
+            observers: GraphObservers::default(),
         }
     }
 
@@ -24,9 +29,12 @@ impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
                 inputs: Vec::default(),
                 outputs: Vec::default(),
                 user_data,
+                bypassed: false,
             }
         });
 
+        self.dirty_nodes.insert(node_id);
+        self.notify(GraphEvent::NodeAdded(node_id));
         f(self, node_id);
 
         node_id
@@ -41,42 +49,270 @@ impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
         kind: InputParamKind,
         shown_inline: bool,
     ) -> InputId {
-        let input_id = self.inputs.insert_with_key(|input_id| InputParam {
-            id: input_id,
+        let index = self.nodes[node_id].inputs.len();
+        self.insert_input_param(node_id, index, name, typ, value, kind, shown_inline)
+    }
+
+    /// Like [`Graph::add_input_param`], but the parameter is inserted at
+    /// `index` in the inputs of the node, instead of after the existing ones.
+    ///
+    /// # Panics
+    /// If `index` is greater than the number of inputs of the node.
+    #[allow(clippy::too_many_arguments)]
+    pub fn insert_input_param(
+        &mut self,
+        node_id: NodeId,
+        index: usize,
+        name: String,
+        typ: DataType,
+        value: ValueType,
+        kind: InputParamKind,
+        shown_inline: bool,
+    ) -> InputId {
+        let param = InputParam {
+            id: InputId::default(),
             typ,
             value,
             kind,
             node: node_id,
             shown_inline,
+            cardinality: InputCardinality::Single,
+            wildcard: None,
+            variadic: None,
+        };
+        self.insert_input_param_like(node_id, index, name, param)
+    }
+
+    /// Adds an input parameter to `node_id` with the settings of `param`,
+    /// which may come from another node or graph.
+    pub(crate) fn add_input_param_like(
+        &mut self,
+        node_id: NodeId,
+        name: String,
+        param: InputParam<DataType, ValueType>,
+    ) -> InputId {
+        let index = self.nodes[node_id].inputs.len();
+        self.insert_input_param_like(node_id, index, name, param)
+    }
+
+    /// Like [`Graph::add_input_param_like`], inserting the parameter at
+    /// `index` in the inputs of the node.
+    pub(crate) fn insert_input_param_like(
+        &mut self,
+        node_id: NodeId,
+        index: usize,
+        name: String,
+        param: InputParam<DataType, ValueType>,
+    ) -> InputId {
+        let input_id = self.inputs.insert_with_key(|input_id| InputParam {
+            id: input_id,
+            node: node_id,
+            ..param
+        });
+        self.nodes[node_id].inputs.insert(index, (name, input_id));
+        self.mark_dirty(node_id);
+        self.notify(GraphEvent::InputAdded {
+            node: node_id,
+            input: input_id,
         });
-        self.nodes[node_id].inputs.push((name, input_id));
+        input_id
+    }
+
+    /// Like [`Graph::add_input_param`], but the created parameter accepts any
+    /// number of incoming connections. See [`InputCardinality::Many`].
+    pub fn add_multi_input_param(
+        &mut self,
+        node_id: NodeId,
+        name: String,
+        typ: DataType,
+        value: ValueType,
+        kind: InputParamKind,
+        shown_inline: bool,
+    ) -> InputId {
+        let input_id = self.add_input_param(node_id, name, typ, value, kind, shown_inline);
+        self.inputs[input_id].cardinality = InputCardinality::Many;
         input_id
     }
 
     pub fn remove_input_param(&mut self, param: InputId) {
         let node = self[param].node;
+        self.mark_dirty(node);
         self[node].inputs.retain(|(_, id)| *id != param);
+        self.remove_all_connections(param);
         self.inputs.remove(param);
-        self.connections.retain(|i, _| i != param);
+        self.notify(GraphEvent::InputRemoved { node, input: param });
     }
 
     pub fn remove_output_param(&mut self, param: OutputId) {
         let node = self[param].node;
+        self.mark_dirty(node);
         self[node].outputs.retain(|(_, id)| *id != param);
+        for input in self.outgoing(param).to_vec() {
+            self.disconnect(param, input);
+        }
         self.outputs.remove(param);
-        self.connections.retain(|_, o| *o != param);
+        self.notify(GraphEvent::OutputRemoved {
+            node,
+            output: param,
+        });
     }
 
     pub fn add_output_param(&mut self, node_id: NodeId, name: String, typ: DataType) -> OutputId {
+        let index = self.nodes[node_id].outputs.len();
+        self.insert_output_param(node_id, index, name, typ)
+    }
+
+    /// Like [`Graph::add_output_param`], but the parameter is inserted at
+    /// `index` in the outputs of the node, instead of after the existing ones.
+    ///
+    /// # Panics
+    /// If `index` is greater than the number of outputs of the node.
+    pub fn insert_output_param(
+        &mut self,
+        node_id: NodeId,
+        index: usize,
+        name: String,
+        typ: DataType,
+    ) -> OutputId {
+        let param = OutputParam {
+            id: OutputId::default(),
+            node: node_id,
+            typ,
+            wildcard: None,
+        };
+        self.insert_output_param_like(node_id, index, name, param)
+    }
+
+    /// Adds an output parameter to `node_id` with the settings of `param`,
+    /// which may come from another node or graph.
+    pub(crate) fn add_output_param_like(
+        &mut self,
+        node_id: NodeId,
+        name: String,
+        param: OutputParam<DataType>,
+    ) -> OutputId {
+        let index = self.nodes[node_id].outputs.len();
+        self.insert_output_param_like(node_id, index, name, param)
+    }
+
+    /// Like [`Graph::add_output_param_like`], inserting the parameter at
+    /// `index` in the outputs of the node.
+    pub(crate) fn insert_output_param_like(
+        &mut self,
+        node_id: NodeId,
+        index: usize,
+        name: String,
+        param: OutputParam<DataType>,
+    ) -> OutputId {
         let output_id = self.outputs.insert_with_key(|output_id| OutputParam {
             id: output_id,
             node: node_id,
-            typ,
+            ..param
+        });
+        self.nodes[node_id].outputs.insert(index, (name, output_id));
+        self.mark_dirty(node_id);
+        self.notify(GraphEvent::OutputAdded {
+            node: node_id,
+            output: output_id,
         });
-        self.nodes[node_id].outputs.push((name, output_id));
         output_id
     }
 
+    /// Renames an input parameter. Fails with
+    /// [`EguiGraphError::DuplicateParameterName`], leaving the graph
+    /// untouched, if another input of the node already has that name.
+    pub fn rename_input_param(
+        &mut self,
+        param: InputId,
+        name: String,
+    ) -> Result<(), EguiGraphError> {
+        let node = self[param].node;
+        if self[node]
+            .inputs
+            .iter()
+            .any(|(other, id)| *id != param && *other == name)
+        {
+            return Err(EguiGraphError::DuplicateParameterName(node, name));
+        }
+        self.set_input_name(param, name);
+        Ok(())
+    }
+
+    /// Renames an input parameter, without checking the names of the other
+    /// inputs of the node.
+    pub(crate) fn set_input_name(&mut self, param: InputId, name: String) {
+        let node = self[param].node;
+        self.mark_dirty(node);
+        if let Some(entry) = self.nodes[node]
+            .inputs
+            .iter_mut()
+            .find(|(_, id)| *id == param)
+        {
+            entry.0 = name;
+        }
+        self.notify(GraphEvent::ParamChanged(param.into()));
+    }
+
+    /// Renames an output parameter. Fails with
+    /// [`EguiGraphError::DuplicateParameterName`], leaving the graph
+    /// untouched, if another output of the node already has that name.
+    pub fn rename_output_param(
+        &mut self,
+        param: OutputId,
+        name: String,
+    ) -> Result<(), EguiGraphError> {
+        let node = self[param].node;
+        if self[node]
+            .outputs
+            .iter()
+            .any(|(other, id)| *id != param && *other == name)
+        {
+            return Err(EguiGraphError::DuplicateParameterName(node, name));
+        }
+        self.mark_dirty(node);
+        if let Some(entry) = self.nodes[node]
+            .outputs
+            .iter_mut()
+            .find(|(_, id)| *id == param)
+        {
+            entry.0 = name;
+        }
+        self.notify(GraphEvent::ParamChanged(param.into()));
+        Ok(())
+    }
+
+    /// Moves an input parameter to `index` in the inputs of its node. The
+    /// other inputs keep their relative order.
+    ///
+    /// # Panics
+    /// If `index` is not lower than the number of inputs of the node.
+    pub fn move_input_param(&mut self, param: InputId, index: usize) {
+        let node = self[param].node;
+        self.mark_dirty(node);
+        let inputs = &mut self.nodes[node].inputs;
+        if let Some(current) = inputs.iter().position(|(_, id)| *id == param) {
+            let entry = inputs.remove(current);
+            inputs.insert(index, entry);
+        }
+        self.notify(GraphEvent::ParamChanged(param.into()));
+    }
+
+    /// Moves an output parameter to `index` in the outputs of its node. The
+    /// other outputs keep their relative order.
+    ///
+    /// # Panics
+    /// If `index` is not lower than the number of outputs of the node.
+    pub fn move_output_param(&mut self, param: OutputId, index: usize) {
+        let node = self[param].node;
+        self.mark_dirty(node);
+        let outputs = &mut self.nodes[node].outputs;
+        if let Some(current) = outputs.iter().position(|(_, id)| *id == param) {
+            let entry = outputs.remove(current);
+            outputs.insert(index, entry);
+        }
+        self.notify(GraphEvent::ParamChanged(param.into()));
+    }
+
     /// Removes a node from the graph with given `node_id`. This also removes
     /// any incoming or outgoing connections from that node
     ///
@@ -84,49 +320,264 @@ impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
     /// after deleting this node as input-output pairs. Note that one of the two
     /// ids in the pair (the one on `node_id`'s end) will be invalid after
     /// calling this function.
+    ///
+    /// If the node is a group node, its nested graph is discarded too.
     pub fn remove_node(&mut self, node_id: NodeId) -> (Node<NodeData>, Vec<(InputId, OutputId)>) {
         let mut disconnect_events = vec![];
+        // The removed node stays in the dirty set, so that evaluators can
+        // discard its cached results.
+        self.mark_dirty(node_id);
 
-        self.connections.retain(|i, o| {
-            if self.outputs[*o].node == node_id || self.inputs[i].node == node_id {
-                disconnect_events.push((i, *o));
-                false
-            } else {
-                true
+        // NOTE: Collect is needed because we can't borrow the param ids while
+        // we remove the connections.
+        for input in self[node_id].input_ids().collect::<SVec<_>>() {
+            for output in self.remove_all_connections(input) {
+                disconnect_events.push((input, output));
             }
-        });
+        }
+        for output in self[node_id].output_ids().collect::<SVec<_>>() {
+            for input in self.outgoing(output).to_vec() {
+                self.disconnect(output, input);
+                disconnect_events.push((input, output));
+            }
+        }
 
         // NOTE: Collect is needed because we can't borrow the input ids while
         // we remove them inside the loop.
         for input in self[node_id].input_ids().collect::<SVec<_>>() {
             self.inputs.remove(input);
+            self.notify(GraphEvent::InputRemoved {
+                node: node_id,
+                input,
+            });
         }
         for output in self[node_id].output_ids().collect::<SVec<_>>() {
             self.outputs.remove(output);
+            self.notify(GraphEvent::OutputRemoved {
+                node: node_id,
+                output,
+            });
         }
         let removed_node = self.nodes.remove(node_id).expect("Node should exist");
+        self.groups.remove(node_id);
+        self.notify(GraphEvent::NodeRemoved(node_id));
 
         (removed_node, disconnect_events)
     }
 
+    /// Removes the connections of `input_id`. Returns the output it was
+    /// connected to, or the first one for inputs with multiple connections.
+    /// Use [`Graph::remove_all_connections`] to get all of them.
     pub fn remove_connection(&mut self, input_id: InputId) -> Option<OutputId> {
-        self.connections.remove(input_id)
+        self.remove_all_connections(input_id).into_iter().next()
+    }
+
+    /// Removes all the connections of `input_id`, returning the outputs it
+    /// was connected to.
+    pub fn remove_all_connections(&mut self, input_id: InputId) -> Vec<OutputId> {
+        let outputs = self.connections.remove(input_id).unwrap_or_default();
+        if !outputs.is_empty() {
+            self.mark_dirty(self[input_id].node);
+        }
+        for output in outputs.iter().copied() {
+            self.unindex_connection(output, input_id);
+            self.notify(GraphEvent::Disconnected {
+                output,
+                input: input_id,
+            });
+        }
+        outputs
+    }
+
+    /// Removes the connection between `output` and `input`, leaving any other
+    /// connection of `input` untouched. Returns whether the connection existed.
+    pub fn disconnect(&mut self, output: OutputId, input: InputId) -> bool {
+        let Some(outputs) = self.connections.get_mut(input) else {
+            return false;
+        };
+        let len_before = outputs.len();
+        outputs.retain(|o| *o != output);
+        let removed = outputs.len() != len_before;
+        if outputs.is_empty() {
+            self.connections.remove(input);
+        }
+        if removed {
+            self.mark_dirty(self[input].node);
+            self.unindex_connection(output, input);
+            self.notify(GraphEvent::Disconnected { output, input });
+        }
+        removed
     }
 
     pub fn iter_nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
         self.nodes.iter().map(|(id, _)| id)
     }
 
+    /// Connects `output` to `input`. For inputs with a single connection,
+    /// this replaces the existing connection. For inputs accepting many
+    /// connections, the new connection is added after the existing ones,
+    /// unless the two parameters are already connected.
     pub fn add_connection(&mut self, output: OutputId, input: InputId) {
-        self.connections.insert(input, output);
+        match self.inputs[input].cardinality {
+            InputCardinality::Single => {
+                let previous = self
+                    .connections
+                    .insert(input, vec![output])
+                    .unwrap_or_default();
+                for previous_output in previous.iter().copied() {
+                    self.unindex_connection(previous_output, input);
+                    if previous_output != output {
+                        self.notify(GraphEvent::Disconnected {
+                            output: previous_output,
+                            input,
+                        });
+                    }
+                }
+                self.index_connection(output, input);
+                if !previous.contains(&output) {
+                    self.notify(GraphEvent::Connected { output, input });
+                }
+            }
+            InputCardinality::Many => {
+                let outputs = self
+                    .connections
+                    .entry(input)
+                    .expect("Input should exist")
+                    .or_default();
+                if !outputs.contains(&output) {
+                    outputs.push(output);
+                    self.index_connection(output, input);
+                    self.notify(GraphEvent::Connected { output, input });
+                }
+            }
+        }
+        self.mark_dirty(self[input].node);
+    }
+
+    /// Marks `node_id` as dirty, together with every node downstream from it.
+    /// This is done automatically by the methods modifying the graph, but
+    /// must be called after changing an [`InputParam::value`] or the
+    /// `user_data` of a node directly.
+    pub fn mark_dirty(&mut self, node_id: NodeId) {
+        if self.dirty_nodes.contains(&node_id) {
+            // The nodes downstream were marked along with it.
+            return;
+        }
+
+        let mut stack = vec![node_id];
+        while let Some(node) = stack.pop() {
+            if self.dirty_nodes.insert(node) {
+                stack.extend(self.successors(node));
+            }
+        }
+    }
+
+    /// Marks all the nodes in the graph as dirty.
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty_nodes.extend(self.nodes.keys());
     }
 
+    pub fn is_dirty(&self, node_id: NodeId) -> bool {
+        self.dirty_nodes.contains(&node_id)
+    }
+
+    /// Returns the nodes that became dirty since the last call, and marks all
+    /// nodes as clean. The result may contain nodes that have been removed
+    /// from the graph since.
+    pub fn take_dirty_nodes(&mut self) -> HashSet<NodeId> {
+        std::mem::take(&mut self.dirty_nodes)
+    }
+
+    /// Iterates all the connections in the graph, as `(input, output)` pairs.
+    /// Inputs with multiple connections appear once per connection.
     pub fn iter_connections(&self) -> impl Iterator<Item = (InputId, OutputId)> + '_ {
-        self.connections.iter().map(|(o, i)| (o, *i))
+        self.connections
+            .iter()
+            .flat_map(|(i, outputs)| outputs.iter().map(move |o| (i, *o)))
     }
 
+    /// Returns the output connected to `input`, or the first one for inputs
+    /// with multiple connections.
     pub fn connection(&self, input: InputId) -> Option<OutputId> {
-        self.connections.get(input).copied()
+        self.input_connections(input).first().copied()
+    }
+
+    /// Returns all the outputs connected to `input`, in connection order.
+    pub fn input_connections(&self, input: InputId) -> &[OutputId] {
+        self.connections
+            .get(input)
+            .map(|outputs| outputs.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Returns all the inputs connected to `output`, in connection order.
+    pub fn outgoing(&self, output: OutputId) -> &[InputId] {
+        self.outgoing_connections
+            .get(output)
+            .map(|inputs| inputs.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Returns the nodes receiving a connection from any output of `node_id`,
+    /// without duplicates.
+    pub fn successors(&self, node_id: NodeId) -> Vec<NodeId> {
+        let mut successors = Vec::new();
+        for output in self[node_id].output_ids() {
+            for input in self.outgoing(output) {
+                let node = self[*input].node;
+                if !successors.contains(&node) {
+                    successors.push(node);
+                }
+            }
+        }
+        successors
+    }
+
+    /// Returns the nodes connected to any input of `node_id`, without
+    /// duplicates.
+    pub fn predecessors(&self, node_id: NodeId) -> Vec<NodeId> {
+        let mut predecessors = Vec::new();
+        for input in self[node_id].input_ids() {
+            for output in self.input_connections(input) {
+                let node = self[*output].node;
+                if !predecessors.contains(&node) {
+                    predecessors.push(node);
+                }
+            }
+        }
+        predecessors
+    }
+
+    /// Rebuilds [`Graph::outgoing_connections`] from [`Graph::connections`].
+    /// Only needed after modifying the connections directly.
+    pub fn rebuild_outgoing_connections(&mut self) {
+        self.outgoing_connections.clear();
+        for (input, outputs) in &self.connections {
+            for output in outputs {
+                self.outgoing_connections
+                    .entry(*output)
+                    .expect("Connected output should exist")
+                    .or_default()
+                    .push(input);
+            }
+        }
+    }
+
+    fn index_connection(&mut self, output: OutputId, input: InputId) {
+        self.outgoing_connections
+            .entry(output)
+            .expect("Output should exist")
+            .or_default()
+            .push(input);
+    }
+
+    fn unindex_connection(&mut self, output: OutputId, input: InputId) {
+        if let Some(inputs) = self.outgoing_connections.get_mut(output) {
+            inputs.retain(|i| *i != input);
+            if inputs.is_empty() {
+                self.outgoing_connections.remove(output);
+            }
+        }
     }
 
     pub fn any_param_type(&self, param: AnyParameterId) -> Result<&DataType, EguiGraphError> {
@@ -160,6 +611,67 @@ impl<NodeData, DataType, ValueType> Default for Graph<NodeData, DataType, ValueT
     }
 }
 
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
+    /// Changes the data type of an input parameter. Connections from outputs
+    /// that can't connect to the new type are removed, see
+    /// [`DataTypeTrait::can_connect_to`].
+    ///
+    /// Returns the removed connections as input-output pairs, like
+    /// [`Graph::remove_node`]. The editor reports removed connections with a
+    /// [`NodeResponse::DisconnectEvent`], user code may want to do the same.
+    pub fn set_input_param_type<UserState>(
+        &mut self,
+        param: InputId,
+        typ: DataType,
+        user_state: &mut UserState,
+    ) -> Vec<(InputId, OutputId)>
+    where
+        DataType: DataTypeTrait<UserState>,
+    {
+        self.inputs[param].typ = typ;
+        self.mark_dirty(self[param].node);
+        self.notify(GraphEvent::ParamChanged(param.into()));
+
+        let mut disconnect_events = vec![];
+        for output in self.input_connections(param).to_vec() {
+            if !self[output]
+                .typ
+                .can_connect_to(&self[param].typ, user_state)
+            {
+                self.disconnect(output, param);
+                disconnect_events.push((param, output));
+            }
+        }
+        disconnect_events
+    }
+
+    /// Changes the data type of an output parameter. Connections to inputs
+    /// the new type can't connect to are removed. See
+    /// [`Graph::set_input_param_type`].
+    pub fn set_output_param_type<UserState>(
+        &mut self,
+        param: OutputId,
+        typ: DataType,
+        user_state: &mut UserState,
+    ) -> Vec<(InputId, OutputId)>
+    where
+        DataType: DataTypeTrait<UserState>,
+    {
+        self.outputs[param].typ = typ;
+        self.mark_dirty(self[param].node);
+        self.notify(GraphEvent::ParamChanged(param.into()));
+
+        let mut disconnect_events = vec![];
+        for input in self.outgoing(param).to_vec() {
+            if !self[param].typ.can_connect_to(&self[input].typ, user_state) {
+                self.disconnect(param, input);
+                disconnect_events.push((input, param));
+            }
+        }
+        disconnect_events
+    }
+}
+
 impl<NodeData> Node<NodeData> {
     pub fn inputs<'a, DataType, DataValue>(
         &'a self,
@@ -212,4 +724,279 @@ impl<DataType, ValueType> InputParam<DataType, ValueType> {
     pub fn node(&self) -> NodeId {
         self.node
     }
+
+    pub fn cardinality(&self) -> InputCardinality {
+        self.cardinality
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn input_names(graph: &TestGraph, node: NodeId) -> Vec<&str> {
+        graph[node]
+            .inputs
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    #[test]
+    pub fn test_insert_and_move_input_param() {
+        let mut graph = TestGraph::new();
+        let a = add_node(&mut graph, &[]);
+        let b = add_node(&mut graph, &["x", "y"]);
+        let a_out = graph[a].get_output("out").unwrap();
+        let x = graph[b].get_input("x").unwrap();
+        let y = graph[b].get_input("y").unwrap();
+        graph.add_connection(a_out, x);
+
+        let z = graph.insert_input_param(
+            b,
+            1,
+            "z".into(),
+            Type::Float,
+            (),
+            InputParamKind::ConnectionOrConstant,
+            true,
+        );
+        assert_eq!(input_names(&graph, b), vec!["x", "z", "y"]);
+        assert_eq!(graph[z].node, b);
+        assert_eq!(graph.input_connections(x), &[a_out]);
+        assert!(graph.input_connections(z).is_empty());
+
+        graph.move_input_param(x, 2);
+        assert_eq!(input_names(&graph, b), vec!["z", "y", "x"]);
+        graph.move_input_param(y, 0);
+        assert_eq!(input_names(&graph, b), vec!["y", "z", "x"]);
+        // Moving a parameter keeps its connections
+        assert_eq!(graph.input_connections(x), &[a_out]);
+        assert_eq!(graph.outgoing(a_out), &[x]);
+    }
+
+    #[test]
+    pub fn test_rename_params() {
+        let mut graph = TestGraph::new();
+        let a = add_node(&mut graph, &["x"]);
+        let b = add_node(&mut graph, &["x"]);
+        let a_out = graph[a].get_output("out").unwrap();
+        let b_in = graph[b].get_input("x").unwrap();
+        graph.add_connection(a_out, b_in);
+
+        graph.rename_input_param(b_in, "renamed".into()).unwrap();
+        graph.rename_output_param(a_out, "result".into()).unwrap();
+        assert_eq!(graph[b].get_input("renamed").unwrap(), b_in);
+        assert!(graph[b].get_input("x").is_err());
+        assert_eq!(graph[a].get_output("result").unwrap(), a_out);
+        assert!(graph[a].get_output("out").is_err());
+        // Renaming a parameter keeps its connections
+        assert_eq!(graph.input_connections(b_in), &[a_out]);
+
+        // Names must be unique on each side of the node, but inputs and
+        // outputs may share one
+        let other = graph.add_input_param(
+            b,
+            "other".into(),
+            Type::Float,
+            (),
+            InputParamKind::ConnectionOnly,
+            true,
+        );
+        assert!(matches!(
+            graph.rename_input_param(other, "renamed".into()),
+            Err(EguiGraphError::DuplicateParameterName(node, name)) if node == b && name == "renamed"
+        ));
+        assert_eq!(graph[b].get_input("other").unwrap(), other);
+        assert!(graph.rename_input_param(other, "out".into()).is_ok());
+        assert!(graph.rename_input_param(b_in, "renamed".into()).is_ok());
+        graph.add_output_param(a, "second".into(), Type::Float);
+        assert!(graph.rename_output_param(a_out, "second".into()).is_err());
+        assert_eq!(graph[a].get_output("result").unwrap(), a_out);
+    }
+
+    #[test]
+    pub fn test_set_param_type() {
+        let mut graph = TestGraph::new();
+        let a = add_node(&mut graph, &[]);
+        let b = add_node(&mut graph, &[]);
+        let c = add_node(&mut graph, &["x", "y"]);
+        let a_out = graph[a].get_output("out").unwrap();
+        let b_out = graph[b].get_output("out").unwrap();
+        let x = graph[c].get_input("x").unwrap();
+        let y = graph[c].get_input("y").unwrap();
+        graph.add_connection(a_out, x);
+        graph.add_connection(a_out, y);
+
+        // Same type: Nothing is disconnected
+        assert_eq!(graph.set_input_param_type(x, Type::Float, &mut ()), vec![]);
+        assert_eq!(graph.input_connections(x), &[a_out]);
+
+        // Only the connection to the retyped input is dropped
+        assert_eq!(
+            graph.set_input_param_type(x, Type::Vec2, &mut ()),
+            vec![(x, a_out)]
+        );
+        assert!(graph.input_connections(x).is_empty());
+        assert_eq!(graph.input_connections(y), &[a_out]);
+        assert_eq!(graph.outgoing(a_out), &[y]);
+
+        // Retyping an output drops the connections it can't make anymore
+        assert_eq!(
+            graph.set_output_param_type(b_out, Type::Vec2, &mut ()),
+            vec![]
+        );
+        graph.add_connection(b_out, x);
+        assert_eq!(
+            graph.set_output_param_type(a_out, Type::Vec2, &mut ()),
+            vec![(y, a_out)]
+        );
+        assert!(graph.input_connections(y).is_empty());
+        assert!(graph.outgoing(a_out).is_empty());
+        assert_eq!(
+            graph.set_output_param_type(b_out, Type::Float, &mut ()),
+            vec![(x, b_out)]
+        );
+        assert!(graph.input_connections(x).is_empty());
+        assert!(graph.outgoing(b_out).is_empty());
+    }
+
+    fn add_multi_node(graph: &mut TestGraph) -> NodeId {
+        graph.add_node("node".into(), (), |graph, node_id| {
+            graph.add_multi_input_param(
+                node_id,
+                "in".into(),
+                Type::Float,
+                (),
+                InputParamKind::ConnectionOnly,
+                true,
+            );
+        })
+    }
+
+    #[test]
+    pub fn test_multiple_connections() {
+        let mut graph = TestGraph::new();
+        let a = add_node(&mut graph, &[]);
+        let b = add_node(&mut graph, &[]);
+        let c = add_node(&mut graph, &[]);
+        let d = add_multi_node(&mut graph);
+        let a_out = graph[a].get_output("out").unwrap();
+        let b_out = graph[b].get_output("out").unwrap();
+        let c_out = graph[c].get_output("out").unwrap();
+        let d_in = graph[d].get_input("in").unwrap();
+
+        // Wires are kept in connection order, without duplicates
+        graph.add_connection(b_out, d_in);
+        graph.add_connection(a_out, d_in);
+        graph.add_connection(c_out, d_in);
+        graph.add_connection(a_out, d_in);
+        assert_eq!(graph.input_connections(d_in), &[b_out, a_out, c_out]);
+        assert_eq!(graph.connection(d_in), Some(b_out));
+        assert_eq!(graph.iter_connections().count(), 3);
+
+        // Disconnecting one wire leaves the others in place
+        assert!(graph.disconnect(a_out, d_in));
+        assert!(!graph.disconnect(a_out, d_in));
+        assert_eq!(graph.input_connections(d_in), &[b_out, c_out]);
+        assert!(graph.outgoing(a_out).is_empty());
+        assert_eq!(graph.outgoing(b_out), &[d_in]);
+        assert_eq!(graph.outgoing(c_out), &[d_in]);
+
+        assert_eq!(graph.remove_all_connections(d_in), vec![b_out, c_out]);
+        assert!(graph.input_connections(d_in).is_empty());
+        assert!(graph.outgoing(b_out).is_empty());
+    }
+
+    /// Checks the outgoing connections against an index rebuilt from the
+    /// connections of `graph`.
+    fn assert_outgoing_index(graph: &TestGraph) {
+        let sorted = |graph: &TestGraph| {
+            graph
+                .outgoing_connections
+                .iter()
+                .map(|(output, inputs)| {
+                    let mut inputs = inputs.clone();
+                    inputs.sort();
+                    (output, inputs)
+                })
+                .collect::<std::collections::BTreeMap<_, _>>()
+        };
+        let mut rebuilt = graph.clone();
+        rebuilt.rebuild_outgoing_connections();
+        assert_eq!(sorted(graph), sorted(&rebuilt));
+    }
+
+    #[test]
+    pub fn test_outgoing_index() {
+        let mut graph = TestGraph::new();
+        let a = add_node(&mut graph, &[]);
+        let b = add_node(&mut graph, &["x", "y"]);
+        let c = add_node(&mut graph, &["x"]);
+        let d = add_multi_node(&mut graph);
+        let a_out = graph[a].get_output("out").unwrap();
+        let b_out = graph[b].get_output("out").unwrap();
+        let c_out = graph[c].get_output("out").unwrap();
+        let b_x = graph[b].get_input("x").unwrap();
+        let b_y = graph[b].get_input("y").unwrap();
+        let c_x = graph[c].get_input("x").unwrap();
+        let d_in = graph[d].get_input("in").unwrap();
+        graph.add_connection(a_out, b_x);
+        graph.add_connection(a_out, b_y);
+        graph.add_connection(a_out, d_in);
+        graph.add_connection(b_out, c_x);
+        graph.add_connection(b_out, d_in);
+        graph.add_connection(c_out, d_in);
+        assert_outgoing_index(&graph);
+
+        assert!(graph.disconnect(a_out, b_y));
+        assert_outgoing_index(&graph);
+
+        graph.remove_output_param(c_out);
+        assert_eq!(graph.input_connections(d_in), &[a_out, b_out]);
+        assert_outgoing_index(&graph);
+
+        graph.remove_node(b);
+        assert_eq!(graph.outgoing(a_out), &[d_in]);
+        assert_outgoing_index(&graph);
+
+        #[cfg(feature = "persistence")]
+        {
+            let text = serde_json::to_string(&graph).unwrap();
+            let loaded: TestGraph = serde_json::from_str(&text).unwrap();
+            assert_eq!(loaded.outgoing(a_out), &[d_in]);
+            assert_outgoing_index(&loaded);
+        }
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    pub fn test_deserialize_connections() {
+        let mut graph = TestGraph::new();
+        let a = add_node(&mut graph, &[]);
+        let b = add_node(&mut graph, &["x"]);
+        let c = add_multi_node(&mut graph);
+        let a_out = graph[a].get_output("out").unwrap();
+        let b_out = graph[b].get_output("out").unwrap();
+        let b_in = graph[b].get_input("x").unwrap();
+        let c_in = graph[c].get_input("in").unwrap();
+        graph.add_connection(a_out, b_in);
+        graph.add_connection(b_out, c_in);
+        graph.add_connection(a_out, c_in);
+
+        // Graphs saved before inputs had multiple connections stored a single
+        // output per input
+        let mut value = serde_json::to_value(&graph).unwrap();
+        for slot in value["connections"].as_array_mut().unwrap() {
+            if let Some(outputs) = slot["value"].as_array().filter(|o| o.len() == 1) {
+                slot["value"] = outputs[0].clone();
+            }
+        }
+        let loaded: TestGraph = serde_json::from_value(value).unwrap();
+        assert_eq!(loaded.input_connections(b_in), &[a_out]);
+        assert_eq!(loaded.input_connections(c_in), &[b_out, a_out]);
+        assert_eq!(loaded.outgoing(a_out), graph.outgoing(a_out));
+        assert_eq!(loaded.outgoing(b_out), &[c_in]);
+    }
 }
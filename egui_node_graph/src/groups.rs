@@ -0,0 +1,666 @@
+use super::*;
+use std::collections::HashSet;
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
+    pub fn is_group(&self, node_id: NodeId) -> bool {
+        self.groups.contains_key(node_id)
+    }
+}
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType>
+where
+    DataType: Clone,
+    ValueType: Clone,
+{
+    /// Moves `nodes` into the nested graph of a new group node. Returns the id
+    /// of the group node, and the new id of each moved node inside the group.
+    ///
+    /// Connections between the grouped nodes are kept inside the group.
+    /// Connections crossing the boundary of the group are rerouted through
+    /// the group node: Each output feeding grouped nodes from outside gets a
+    /// group input, and each grouped output feeding nodes outside gets a group
+    /// output.
+    ///
+    /// The moved nodes are drawn in the order of `nodes`, and their positions
+    /// are reset. Use [`GraphEditorState::group_nodes`] to keep their draw
+    /// order and positions.
+    ///
+    /// The ports of the group node are fixed at creation, see [`NodeGroup`].
+    /// Use [`Graph::ungroup_node`] to undo the grouping.
+    pub fn group_nodes(
+        &mut self,
+        nodes: &[NodeId],
+        label: String,
+        user_data: NodeData,
+    ) -> (NodeId, SecondaryMap<NodeId, NodeId>) {
+        let mut grouped = HashSet::new();
+        let nodes: Vec<NodeId> = nodes
+            .iter()
+            .copied()
+            .filter(|node| self.nodes.contains_key(*node) && grouped.insert(*node))
+            .collect();
+
+        // Sort the connections of the grouped nodes, as `(input, output)`
+        // pairs, before removing them.
+        let mut internal = vec![];
+        let mut incoming = vec![];
+        let mut outgoing = vec![];
+        for node in nodes.iter().copied() {
+            for input in self[node].input_ids() {
+                for output in self.input_connections(input).iter().copied() {
+                    if grouped.contains(&self[output].node) {
+                        internal.push((input, output));
+                    } else {
+                        incoming.push((input, output));
+                    }
+                }
+            }
+            for output in self[node].output_ids() {
+                for input in self.outgoing(output).iter().copied() {
+                    if !grouped.contains(&self[input].node) {
+                        outgoing.push((input, output));
+                    }
+                }
+            }
+        }
+        for node in nodes.iter().copied() {
+            // NOTE: Collect is needed because we can't borrow the param ids
+            // while we remove the connections.
+            for input in self[node].input_ids().collect::<SVec<_>>() {
+                self.remove_all_connections(input);
+            }
+            for output in self[node].output_ids().collect::<SVec<_>>() {
+                for input in self.outgoing(output).to_vec() {
+                    self.disconnect(output, input);
+                }
+            }
+        }
+
+        // Move the nodes and their parameters to the nested graph. All ids
+        // change in the process.
+        let mut inner = Graph::new();
+        let mut node_ids = SecondaryMap::new();
+        let mut input_ids = SecondaryMap::new();
+        let mut output_ids = SecondaryMap::new();
+        for node_id in nodes.iter().copied() {
+            self.mark_dirty(node_id);
+            let node = self.nodes.remove(node_id).expect("Node should exist");
+            let new_id = inner.add_node(node.label, node.user_data, |inner, new_id| {
+                for (name, input) in node.inputs {
+                    let param = self.inputs.remove(input).expect("Input should exist");
+                    self.notify(GraphEvent::InputRemoved {
+                        node: node_id,
+                        input,
+                    });
+                    let new_input = inner.add_input_param_like(new_id, name, param);
+                    input_ids.insert(input, new_input);
+                }
+                for (name, output) in node.outputs {
+                    let param = self.outputs.remove(output).expect("Output should exist");
+                    self.notify(GraphEvent::OutputRemoved {
+                        node: node_id,
+                        output,
+                    });
+                    let new_output = inner.add_output_param_like(new_id, name, param);
+                    output_ids.insert(output, new_output);
+                }
+            });
+            self.notify(GraphEvent::NodeRemoved(node_id));
+            inner.set_bypassed(new_id, node.bypassed);
+            node_ids.insert(node_id, new_id);
+
+            if let Some(mut group) = self.groups.remove(node_id) {
+                // The ports of a nested group node have new ids too.
+                group.remap_ports(&input_ids, &output_ids);
+                inner.groups.insert(new_id, group);
+            }
+        }
+        for (input, output) in internal {
+            inner.add_connection(output_ids[output], input_ids[input]);
+        }
+
+        // Create the ports of the group node, and reconnect the surrounding
+        // nodes to them.
+        let group_id = self.add_node(label, user_data, |_, _| {});
+        let mut group_inputs: SecondaryMap<InputId, Vec<InputId>> = SecondaryMap::new();
+        let mut input_for_output: Vec<(OutputId, InputId)> = vec![];
+        for (input, output) in incoming {
+            let inner_input = input_ids[input];
+            let group_input = match input_for_output.iter().find(|(o, _)| *o == output) {
+                Some((_, group_input)) => *group_input,
+                None => {
+                    let param = &inner[inner_input];
+                    let name = unique_name(
+                        &self[group_id].inputs,
+                        param_name(&inner[param.node].inputs, inner_input),
+                    );
+                    let group_input = self.add_input_param(
+                        group_id,
+                        name,
+                        param.typ.clone(),
+                        param.value.clone(),
+                        param.kind,
+                        param.shown_inline,
+                    );
+                    self.add_connection(output, group_input);
+                    input_for_output.push((output, group_input));
+                    group_input
+                }
+            };
+            group_inputs
+                .entry(group_input)
+                .expect("Group input should exist")
+                .or_default()
+                .push(inner_input);
+        }
+        let mut group_outputs: SecondaryMap<OutputId, OutputId> = SecondaryMap::new();
+        for (input, output) in outgoing {
+            let inner_output = output_ids[output];
+            let group_output = match group_outputs.iter().find(|(_, o)| **o == inner_output) {
+                Some((group_output, _)) => group_output,
+                None => {
+                    let param = &inner[inner_output];
+                    let name = unique_name(
+                        &self[group_id].outputs,
+                        param_name(&inner[param.node].outputs, inner_output),
+                    );
+                    let group_output = self.add_output_param(group_id, name, param.typ.clone());
+                    group_outputs.insert(group_output, inner_output);
+                    group_output
+                }
+            };
+            self.add_connection(group_output, input);
+        }
+
+        self.groups.insert(
+            group_id,
+            NodeGroup {
+                node_order: nodes.iter().map(|node| node_ids[*node]).collect(),
+                node_positions: node_ids
+                    .values()
+                    .map(|node| (*node, egui::Pos2::ZERO))
+                    .collect(),
+                frames: SlotMap::default(),
+                frame_order: Vec::new(),
+                knots: WireKnots::default(),
+                file_ids: SecondaryMap::new(),
+                graph: inner,
+                inputs: group_inputs,
+                outputs: group_outputs,
+            },
+        );
+
+        (group_id, node_ids)
+    }
+
+    /// Replaces the group node `group_id` with the nodes of its nested graph,
+    /// undoing [`Graph::group_nodes`]. Returns the new ids of the nested nodes
+    /// and parameters, or `None` if the node is not a group.
+    ///
+    /// The connections of the group node are rerouted to the nested
+    /// parameters its ports stand for, and the values of its unconnected
+    /// inputs are copied to the nested inputs they feed.
+    pub fn ungroup_node(&mut self, group_id: NodeId) -> Option<IdRemap> {
+        let mut group = self.groups.remove(group_id)?;
+
+        let mut incoming = vec![];
+        for (input, inner_inputs) in &group.inputs {
+            if !self.inputs.contains_key(input) {
+                continue;
+            }
+            let sources = self.input_connections(input).to_vec();
+            for inner_input in inner_inputs.iter().copied() {
+                match group.graph.inputs.get_mut(inner_input) {
+                    Some(param) if sources.is_empty() => param.value = self[input].value.clone(),
+                    Some(_) => incoming.push((inner_input, sources.clone())),
+                    None => {}
+                }
+            }
+        }
+        let mut outgoing = vec![];
+        for (output, inner_output) in &group.outputs {
+            if self.outputs.contains_key(output) {
+                outgoing.push((*inner_output, self.outgoing(output).to_vec()));
+            }
+        }
+
+        self.remove_node(group_id);
+        let remap = self.merge(group.graph);
+        for (inner_input, sources) in incoming {
+            if let Some(input) = remap.input(inner_input) {
+                for output in sources {
+                    self.add_connection(output, input);
+                }
+            }
+        }
+        for (inner_output, targets) in outgoing {
+            if let Some(output) = remap.output(inner_output) {
+                for input in targets {
+                    self.add_connection(output, input);
+                }
+            }
+        }
+        Some(remap)
+    }
+}
+
+impl<NodeData, DataType, ValueType> NodeGroup<NodeData, DataType, ValueType> {
+    /// Updates the ids of the group node's ports after the group node was
+    /// moved to another graph. Ports missing from the maps are dropped.
+    pub(crate) fn remap_ports(
+        &mut self,
+        input_ids: &SecondaryMap<InputId, InputId>,
+        output_ids: &SecondaryMap<OutputId, OutputId>,
+    ) {
+        self.inputs = std::mem::take(&mut self.inputs)
+            .into_iter()
+            .filter_map(|(input, inner)| Some((*input_ids.get(input)?, inner)))
+            .collect();
+        self.outputs = std::mem::take(&mut self.outputs)
+            .into_iter()
+            .filter_map(|(output, inner)| Some((*output_ids.get(output)?, inner)))
+            .collect();
+    }
+}
+
+/// Returns the center of `positions`, or the origin if there are none.
+fn center(positions: impl Iterator<Item = egui::Pos2>) -> egui::Pos2 {
+    let (sum, count) = positions.fold((egui::Vec2::ZERO, 0), |(sum, count), pos| {
+        (sum + pos.to_vec2(), count + 1)
+    });
+    if count == 0 {
+        egui::Pos2::ZERO
+    } else {
+        (sum / count as f32).to_pos2()
+    }
+}
+
+/// Returns the name of the parameter `id` in a list of node parameters.
+fn param_name<Id: PartialEq>(params: &[(String, Id)], id: Id) -> &str {
+    params
+        .iter()
+        .find(|(_, param)| *param == id)
+        .map(|(name, _)| name.as_str())
+        .unwrap_or_default()
+}
+
+/// Returns `name`, followed by a number if needed to make it different from
+/// the names of `params`.
+fn unique_name<Id>(params: &[(String, Id)], name: &str) -> String {
+    let is_taken = |candidate: &str| params.iter().any(|(taken, _)| taken == candidate);
+    let mut candidate = name.to_string();
+    let mut n = 2;
+    while is_taken(&candidate) {
+        candidate = format!("{} {}", name, n);
+        n += 1;
+    }
+    candidate
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+{
+    /// Returns the graph being edited: The nested graph of the innermost open
+    /// group, or the root graph if no group is open.
+    pub fn open_graph(&self) -> &Graph<NodeData, DataType, ValueType> {
+        let mut graph = &self.graph;
+        for group_id in &self.open_groups {
+            match graph.groups.get(*group_id) {
+                Some(group) => graph = &group.graph,
+                None => break,
+            }
+        }
+        graph
+    }
+
+    /// Returns the labels of the open group nodes, from the outermost one.
+    pub fn open_group_labels(&self) -> Vec<String> {
+        let mut labels = vec![];
+        let mut graph = &self.graph;
+        for group_id in &self.open_groups {
+            match graph.groups.get(*group_id) {
+                Some(group) => {
+                    labels.push(graph[*group_id].label.clone());
+                    graph = &group.graph;
+                }
+                None => break,
+            }
+        }
+        labels
+    }
+
+    /// Opens the group node `group_id` of the open graph, so its nested graph
+    /// is the one displayed and edited by the editor. Does nothing if the node
+    /// is not a group.
+    pub fn open_group(&mut self, group_id: NodeId) {
+        if self.open_graph().is_group(group_id) {
+            self.open_groups.push(group_id);
+            self.clear_interaction();
+        }
+    }
+
+    /// Closes the innermost open group, going back to the enclosing graph.
+    pub fn close_group(&mut self) {
+        if self.open_groups.pop().is_some() {
+            self.clear_interaction();
+        }
+    }
+
+    /// Closes the open groups until only `depth` of them remain open. A depth
+    /// of zero goes back to the root graph.
+    pub fn close_groups_to(&mut self, depth: usize) {
+        if depth < self.open_groups.len() {
+            self.open_groups.truncate(depth);
+            self.clear_interaction();
+        }
+    }
+
+    /// Drops the open groups that no longer exist, e.g. after an undo.
+    pub fn validate_open_groups(&mut self) {
+        let mut graph = &self.graph;
+        for (depth, group_id) in self.open_groups.iter().enumerate() {
+            match graph.groups.get(*group_id) {
+                Some(group) => graph = &group.graph,
+                None => {
+                    self.open_groups.truncate(depth);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Resets the interactions that refer to the nodes of the open graph.
+    fn clear_interaction(&mut self) {
+        self.selected_nodes.clear();
+        self.connection_in_progress = None;
+        self.ongoing_box_selection = None;
+        self.node_finder = None;
+    }
+
+    /// Calls `f` with the contents of the innermost open group swapped into
+    /// the `graph`, `node_order`, `node_positions`, frames, knots and file ids
+    /// of this state. This allows the editor to work on nested graphs the same
+    /// way it works on the root graph. Meanwhile, the observers of the root
+    /// graph hear about the changes of the nested graph, see
+    /// [`GraphObserverTrait::on_group_event`].
+    pub(crate) fn with_open_group<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        // While a group is swapped in, its `NodeGroup` holds the state of the
+        // enclosing graph instead.
+        let mut levels = vec![];
+        for depth in 0..self.open_groups.len() {
+            let group_id = self.open_groups[depth];
+            let Some(mut group) = self.graph.groups.remove(group_id) else {
+                self.open_groups.truncate(depth);
+                break;
+            };
+            std::mem::swap(&mut self.graph, &mut group.graph);
+            std::mem::swap(&mut self.node_order, &mut group.node_order);
+            std::mem::swap(&mut self.node_positions, &mut group.node_positions);
+            std::mem::swap(&mut self.frames, &mut group.frames);
+            std::mem::swap(&mut self.frame_order, &mut group.frame_order);
+            std::mem::swap(&mut self.knots, &mut group.knots);
+            std::mem::swap(&mut self.file_ids, &mut group.file_ids);
+            levels.push((group_id, group));
+        }
+        // The enclosing graph of the outermost open group is the root graph.
+        let group_path = levels.iter().map(|(group_id, _)| *group_id).collect();
+        if let Some((_, root)) = levels.first_mut() {
+            self.graph
+                .observers
+                .forward_to(&mut root.graph.observers, group_path);
+        }
+
+        let result = f(self);
+
+        if let Some((_, root)) = levels.first_mut() {
+            self.graph
+                .observers
+                .stop_forwarding(&mut root.graph.observers);
+        }
+        for (group_id, mut group) in levels.into_iter().rev() {
+            std::mem::swap(&mut self.graph, &mut group.graph);
+            std::mem::swap(&mut self.node_order, &mut group.node_order);
+            std::mem::swap(&mut self.node_positions, &mut group.node_positions);
+            std::mem::swap(&mut self.frames, &mut group.frames);
+            std::mem::swap(&mut self.frame_order, &mut group.frame_order);
+            std::mem::swap(&mut self.knots, &mut group.knots);
+            std::mem::swap(&mut self.file_ids, &mut group.file_ids);
+            // Edits inside a group change the outputs of the group node.
+            if !group.graph.dirty_nodes.is_empty() {
+                group.graph.dirty_nodes.clear();
+                self.graph.mark_dirty(group_id);
+            }
+            self.graph.groups.insert(group_id, group);
+        }
+        result
+    }
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+where
+    DataType: Clone,
+    ValueType: Clone,
+{
+    /// Collapses `nodes` of the open graph into a new group node, placed at
+    /// the center of the grouped nodes. Returns the id of the group node. See
+    /// [`Graph::group_nodes`].
+    pub fn group_nodes(&mut self, nodes: &[NodeId], label: String, user_data: NodeData) -> NodeId {
+        self.with_open_group(|state| state.group_nodes_in_open_graph(nodes, label, user_data))
+    }
+
+    /// Like [`GraphEditorState::group_nodes`], for a state that has the open
+    /// group swapped in.
+    pub(crate) fn group_nodes_in_open_graph(
+        &mut self,
+        nodes: &[NodeId],
+        label: String,
+        user_data: NodeData,
+    ) -> NodeId {
+        let center = center(
+            nodes
+                .iter()
+                .filter_map(|node| self.node_positions.get(*node).copied()),
+        );
+
+        let (group_id, node_ids) = self.graph.group_nodes(nodes, label, user_data);
+        let group = &mut self.graph.groups[group_id];
+        for (node, new_id) in &node_ids {
+            if let Some(pos) = self.node_positions.remove(node) {
+                group.node_positions.insert(*new_id, pos);
+            }
+        }
+        group.node_order = self
+            .node_order
+            .iter()
+            .filter_map(|node| node_ids.get(*node).copied())
+            .collect();
+
+        self.node_order.retain(|node| !node_ids.contains_key(*node));
+        self.node_order.push(group_id);
+        self.node_positions.insert(group_id, center);
+        self.selected_nodes = vec![group_id];
+        group_id
+    }
+
+    /// Replaces the group node `group_id` of the open graph with its
+    /// contents, see [`Graph::ungroup_node`]. The nested nodes keep their draw
+    /// order and are drawn on top of the existing nodes, centered on the group
+    /// node. The frames and knots of the group are moved along.
+    pub fn ungroup_node(&mut self, group_id: NodeId) -> Option<IdRemap> {
+        self.with_open_group(|state| state.ungroup_node_in_open_graph(group_id))
+    }
+
+    /// Like [`GraphEditorState::ungroup_node`], for a state that has the open
+    /// group swapped in.
+    pub(crate) fn ungroup_node_in_open_graph(&mut self, group_id: NodeId) -> Option<IdRemap> {
+        let group = self.graph.groups.get(group_id)?;
+        let node_order = group.node_order.clone();
+        let node_positions = group.node_positions.clone();
+        let knots = group.knots.clone();
+        let frames = group
+            .frame_order
+            .iter()
+            .filter_map(|frame| group.frames.get(*frame).cloned())
+            .collect();
+        let position = self
+            .node_positions
+            .get(group_id)
+            .copied()
+            .unwrap_or(egui::Pos2::ZERO);
+        let offset = position - center(node_positions.values().copied());
+
+        let remap = self.graph.ungroup_node(group_id)?;
+        self.node_positions.remove(group_id);
+        self.node_order.retain(|node| *node != group_id);
+        self.place_merged_nodes(&remap, &node_order, &node_positions, &knots, frames, offset);
+        self.selected_nodes = remap.nodes.values().copied().collect();
+        Some(remap)
+    }
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserResponse, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+where
+    NodeData: NodeDataTrait<
+        Response = UserResponse,
+        UserState = UserState,
+        DataType = DataType,
+        ValueType = ValueType,
+    >,
+    UserResponse: UserResponseTrait,
+    DataType: Clone,
+    ValueType: Clone,
+{
+    /// Collapses the selected nodes of the open graph into a group node when
+    /// the user presses Ctrl+G over `ui` and no widget has the focus. The
+    /// group node is created with [`NodeDataTrait::group_node_data`]. Call it
+    /// before [`GraphEditorState::draw_graph_editor`] and pass it the returned
+    /// [`NodeResponse::CreatedGroup`].
+    #[must_use]
+    pub fn handle_group_shortcut(
+        &mut self,
+        ui: &egui::Ui,
+        user_state: &mut UserState,
+    ) -> Vec<NodeResponse<UserResponse, NodeData>> {
+        if self.selected_nodes.is_empty()
+            || !ui.rect_contains_pointer(ui.max_rect())
+            || ui.ctx().memory(|mem| mem.focus().is_some())
+            || !ui
+                .ctx()
+                .input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::G))
+        {
+            return vec![];
+        }
+        self.with_open_group(|state| {
+            let nodes = state.selected_nodes.clone();
+            NodeData::group_node_data(&nodes, &state.graph, user_state)
+                .map(|(label, user_data)| {
+                    let group_id = state.group_nodes_in_open_graph(&nodes, label, user_data);
+                    NodeResponse::CreatedGroup(group_id)
+                })
+                .into_iter()
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    pub fn test_group_nodes() {
+        let mut graph = TestGraph::new();
+        let a = add_node(&mut graph, &["in"]);
+        let b = add_node(&mut graph, &["in"]);
+        let c = add_node(&mut graph, &["in"]);
+        let d = add_node(&mut graph, &["in"]);
+        connect(&mut graph, a, b, "in");
+        connect(&mut graph, b, c, "in");
+        connect(&mut graph, c, d, "in");
+
+        let (group_id, node_ids) = graph.group_nodes(&[b, c], "group".into(), ());
+        assert_eq!(graph.nodes.len(), 3);
+        assert_eq!(graph.predecessors(group_id), vec![a]);
+        assert_eq!(graph.successors(group_id), vec![d]);
+
+        let group = &graph.groups[group_id];
+        let (inner_b, inner_c) = (node_ids[b], node_ids[c]);
+        assert_eq!(group.graph.nodes.len(), 2);
+        assert_eq!(group.graph.successors(inner_b), vec![inner_c]);
+        assert_eq!(group.node_order, vec![inner_b, inner_c]);
+
+        let group_input = graph[group_id].get_input("in").unwrap();
+        let group_output = graph[group_id].get_output("out").unwrap();
+        let inner_input = group.graph[inner_b].get_input("in").unwrap();
+        let inner_output = group.graph[inner_c].get_output("out").unwrap();
+        assert_eq!(group.inputs[group_input], vec![inner_input]);
+        assert_eq!(group.outputs[group_output], inner_output);
+
+        // Groups can be nested, and removing a group removes its contents.
+        let (outer_id, node_ids) = graph.group_nodes(&[group_id, d], "outer".into(), ());
+        assert!(graph.groups[outer_id].graph.is_group(node_ids[group_id]));
+        graph.remove_node(outer_id);
+        assert!(graph.groups.is_empty());
+    }
+
+    #[test]
+    pub fn test_ungroup_node() {
+        let mut graph = TestGraph::new();
+        let a = add_node(&mut graph, &["in"]);
+        let b = add_node(&mut graph, &["in"]);
+        let c = add_node(&mut graph, &["in"]);
+        let d = add_node(&mut graph, &["in"]);
+        connect(&mut graph, a, b, "in");
+        connect(&mut graph, b, c, "in");
+        connect(&mut graph, c, d, "in");
+        assert!(graph.ungroup_node(a).is_none());
+
+        let (group_id, node_ids) = graph.group_nodes(&[b, c], "group".into(), ());
+        let remap = graph.ungroup_node(group_id).unwrap();
+        assert!(!graph.nodes.contains_key(group_id));
+        assert!(graph.groups.is_empty());
+        assert_eq!(graph.nodes.len(), 4);
+        let new_b = remap.node(node_ids[b]).unwrap();
+        let new_c = remap.node(node_ids[c]).unwrap();
+        assert_eq!(graph.predecessors(new_b), vec![a]);
+        assert_eq!(graph.successors(new_b), vec![new_c]);
+        assert_eq!(graph.successors(new_c), vec![d]);
+    }
+
+    #[test]
+    pub fn test_ungroup_node_editor_state() {
+        type TestState = GraphEditorState<(), Type, (), (), ()>;
+        let mut state = TestState::default();
+        let a = add_node(&mut state.graph, &["in"]);
+        let b = add_node(&mut state.graph, &["in"]);
+        let c = add_node(&mut state.graph, &["in"]);
+        connect(&mut state.graph, a, b, "in");
+        connect(&mut state.graph, b, c, "in");
+        state.node_order = vec![c, b, a];
+        for (node, x) in [(a, 0.0), (b, 100.0), (c, 200.0)] {
+            state.node_positions.insert(node, egui::pos2(x, 0.0));
+        }
+
+        // The ungrouped nodes keep their draw order, centered on the group
+        // node, and are selected
+        let group_id = state.group_nodes(&[b, c], "group".into(), ());
+        assert_eq!(state.node_positions[group_id], egui::pos2(150.0, 0.0));
+        state.node_positions[group_id] = egui::pos2(150.0, 100.0);
+        let inner_order = state.graph.groups[group_id].node_order.clone();
+        let remap = state.ungroup_node(group_id).unwrap();
+        let (new_c, new_b) = (
+            remap.node(inner_order[0]).unwrap(),
+            remap.node(inner_order[1]).unwrap(),
+        );
+        assert_eq!(state.graph.successors(a), vec![new_b]);
+        assert_eq!(state.graph.successors(new_b), vec![new_c]);
+        assert_eq!(state.node_order, vec![a, new_c, new_b]);
+        assert_eq!(state.node_positions[new_b], egui::pos2(100.0, 100.0));
+        assert_eq!(state.node_positions[new_c], egui::pos2(200.0, 100.0));
+        assert!(!state.node_positions.contains_key(group_id));
+        assert_eq!(state.selected_nodes.len(), 2);
+    }
+}
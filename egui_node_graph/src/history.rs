@@ -0,0 +1,776 @@
+use super::*;
+
+/// Describes the edit that produced an entry in the [`UndoHistory`]. This is
+/// mostly informative (e.g. to display "Undo Delete node" in a menu), but it
+/// is also used to coalesce continuous interactions: Consecutive steps of the
+/// same kind are merged into a single entry while the interaction lasts, so
+/// dragging a node around only produces one undo step.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HistoryStep {
+    CreateNode,
+    DeleteNode,
+    Connect,
+    Disconnect,
+    MoveNodes,
+    GroupNodes,
+    /// A node was bypassed, or enabled again.
+    Bypass,
+    /// The given frame was moved, resized, renamed or recolored.
+    EditFrame(FrameId),
+    DeleteFrame,
+    /// A knot was inserted in a wire, or removed from it.
+    Reroute,
+    MoveKnot,
+    /// The inline value of the given input parameter was edited.
+    EditValue(InputId),
+    /// A custom step, recorded by user code using
+    /// [`GraphEditorState::transaction`].
+    User(String),
+}
+
+impl HistoryStep {
+    /// Returns the history step produced by the given response, if the
+    /// response modifies the editor state in a way that should be undoable.
+    pub fn from_response<UserResponse, NodeData>(
+        response: &NodeResponse<UserResponse, NodeData>,
+    ) -> Option<Self>
+    where
+        UserResponse: UserResponseTrait,
+        NodeData: NodeDataTrait,
+    {
+        match response {
+            NodeResponse::ConnectEventEnded { .. } => Some(Self::Connect),
+            NodeResponse::CreatedNode(_) => Some(Self::CreateNode),
+            NodeResponse::DeleteNodeUi(_) => Some(Self::DeleteNode),
+            NodeResponse::DisconnectEvent { .. } => Some(Self::Disconnect),
+            NodeResponse::MoveNode { .. } => Some(Self::MoveNodes),
+            NodeResponse::InputValueChanged { input, .. } => Some(Self::EditValue(*input)),
+            NodeResponse::CreatedGroup(_) => Some(Self::GroupNodes),
+            NodeResponse::SetBypassed { .. } => Some(Self::Bypass),
+            NodeResponse::MoveFrame { frame, .. }
+            | NodeResponse::ResizeFrame { frame, .. }
+            | NodeResponse::EditFrame(frame) => Some(Self::EditFrame(*frame)),
+            NodeResponse::DeleteFrame(_) => Some(Self::DeleteFrame),
+            NodeResponse::AddKnot { .. } | NodeResponse::RemoveKnot { .. } => Some(Self::Reroute),
+            NodeResponse::MoveKnot { .. } => Some(Self::MoveKnot),
+            NodeResponse::OpenGroup(_)
+            | NodeResponse::ConnectEventStarted(..)
+            | NodeResponse::SelectNode(_)
+            | NodeResponse::DeleteNodeFull { .. }
+            | NodeResponse::RaiseNode(_)
+            | NodeResponse::User(_) => None,
+        }
+    }
+
+    /// Whether consecutive steps of this kind should be merged into a single
+    /// undo step while the interaction producing them is still ongoing.
+    pub fn coalesces_with(&self, other: &HistoryStep) -> bool {
+        match self {
+            HistoryStep::MoveNodes
+            | HistoryStep::MoveKnot
+            | HistoryStep::EditValue(_)
+            | HistoryStep::EditFrame(_) => self == other,
+            _ => false,
+        }
+    }
+
+    /// A short human-readable description of this step.
+    pub fn label(&self) -> &str {
+        match self {
+            HistoryStep::CreateNode => "Create node",
+            HistoryStep::DeleteNode => "Delete node",
+            HistoryStep::Connect => "Connect",
+            HistoryStep::Disconnect => "Disconnect",
+            HistoryStep::MoveNodes => "Move nodes",
+            HistoryStep::GroupNodes => "Group nodes",
+            HistoryStep::Bypass => "Bypass node",
+            HistoryStep::EditFrame(_) => "Edit frame",
+            HistoryStep::DeleteFrame => "Delete frame",
+            HistoryStep::Reroute => "Reroute wire",
+            HistoryStep::MoveKnot => "Move knot",
+            HistoryStep::EditValue(_) => "Edit value",
+            HistoryStep::User(label) => label,
+        }
+    }
+}
+
+/// The parts of the editor state a kind of step changes, and that are stored
+/// by its undo entry.
+#[derive(Clone, Copy, Debug, Default)]
+struct StepParts {
+    graph: bool,
+    node_order: bool,
+    node_positions: bool,
+    frames: bool,
+    knots: bool,
+}
+
+impl HistoryStep {
+    /// The parts of the editor state changed by this step, besides the single
+    /// value changed by [`HistoryStep::EditValue`].
+    fn parts(&self) -> StepParts {
+        let none = StepParts::default();
+        match self {
+            HistoryStep::EditValue(_) => none,
+            HistoryStep::MoveNodes => StepParts {
+                node_positions: true,
+                ..none
+            },
+            HistoryStep::Bypass => StepParts {
+                graph: true,
+                ..none
+            },
+            HistoryStep::EditFrame(_) => StepParts {
+                node_positions: true,
+                frames: true,
+                ..none
+            },
+            HistoryStep::DeleteFrame => StepParts {
+                frames: true,
+                ..none
+            },
+            HistoryStep::Reroute | HistoryStep::MoveKnot => StepParts {
+                knots: true,
+                ..none
+            },
+            HistoryStep::CreateNode
+            | HistoryStep::DeleteNode
+            | HistoryStep::Connect
+            | HistoryStep::Disconnect
+            | HistoryStep::GroupNodes => StepParts {
+                graph: true,
+                node_order: true,
+                node_positions: true,
+                knots: true,
+                ..none
+            },
+            HistoryStep::User(_) => StepParts {
+                graph: true,
+                node_order: true,
+                node_positions: true,
+                frames: true,
+                knots: true,
+            },
+        }
+    }
+}
+
+/// The undoable parts of the state of the open graph: The graph, with the
+/// contents of its group nodes, and its layout. Transient UI state, like the
+/// pan or the node finder, is not part of the snapshot.
+#[derive(Clone, Debug)]
+pub struct EditorSnapshot<NodeData, DataType, ValueType> {
+    /// The open groups when the snapshot was taken, see
+    /// [`GraphEditorState::open_groups`].
+    pub open_groups: Vec<NodeId>,
+    pub graph: Graph<NodeData, DataType, ValueType>,
+    pub node_order: Vec<NodeId>,
+    pub node_positions: SecondaryMap<NodeId, egui::Pos2>,
+    pub frames: SlotMap<FrameId, CommentFrame>,
+    pub frame_order: Vec<FrameId>,
+    pub knots: WireKnots,
+}
+
+/// The parts of the state of an open graph changed by an undo step, as they
+/// are on the other side of the step. Parts that weren't changed are `None`.
+#[derive(Clone, Debug)]
+pub struct EditorDiff<NodeData, DataType, ValueType> {
+    pub graph: Option<Graph<NodeData, DataType, ValueType>>,
+    /// The values of single input parameters. Only used when the whole graph
+    /// isn't stored.
+    pub values: Vec<(InputId, ValueType)>,
+    pub node_order: Option<Vec<NodeId>>,
+    pub node_positions: Option<SecondaryMap<NodeId, egui::Pos2>>,
+    pub frames: Option<(SlotMap<FrameId, CommentFrame>, Vec<FrameId>)>,
+    pub knots: Option<WireKnots>,
+}
+
+impl<NodeData, DataType, ValueType> Default for EditorDiff<NodeData, DataType, ValueType> {
+    fn default() -> Self {
+        Self {
+            graph: None,
+            values: Vec::new(),
+            node_order: None,
+            node_positions: None,
+            frames: None,
+            knots: None,
+        }
+    }
+}
+
+impl<NodeData, DataType, ValueType> EditorDiff<NodeData, DataType, ValueType>
+where
+    NodeData: Clone,
+    DataType: Clone,
+    ValueType: Clone,
+{
+    /// Copies the parts of `snapshot` changed by `step`, unless they are
+    /// stored already.
+    fn add_parts(
+        &mut self,
+        step: &HistoryStep,
+        snapshot: &EditorSnapshot<NodeData, DataType, ValueType>,
+    ) {
+        let parts = step.parts();
+        if parts.graph && self.graph.is_none() {
+            self.graph = Some(snapshot.graph.clone());
+            // The stored graph contains the values from the same state.
+            self.values.clear();
+        }
+        if let HistoryStep::EditValue(input) = step {
+            let stored = self.graph.is_some() || self.values.iter().any(|(id, _)| id == input);
+            if let (false, Some(param)) = (stored, snapshot.graph.inputs.get(*input)) {
+                self.values.push((*input, param.value.clone()));
+            }
+        }
+        if parts.node_order && self.node_order.is_none() {
+            self.node_order = Some(snapshot.node_order.clone());
+        }
+        if parts.node_positions && self.node_positions.is_none() {
+            self.node_positions = Some(snapshot.node_positions.clone());
+        }
+        if parts.frames && self.frames.is_none() {
+            self.frames = Some((snapshot.frames.clone(), snapshot.frame_order.clone()));
+        }
+        if parts.knots && self.knots.is_none() {
+            self.knots = Some(snapshot.knots.clone());
+        }
+    }
+}
+
+impl<NodeData, DataType, ValueType> EditorDiff<NodeData, DataType, ValueType> {
+    /// Exchanges the stored parts with the ones of `state`, which must have
+    /// the open group swapped in. Afterwards, the diff holds the parts as they
+    /// were in `state`, so swapping again reverts the change.
+    fn swap<NodeTemplate, UserState>(
+        &mut self,
+        state: &mut GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>,
+    ) {
+        for (input, value) in &mut self.values {
+            if let Some(param) = state.graph.inputs.get_mut(*input) {
+                std::mem::swap(value, &mut param.value);
+                let node = param.node;
+                state.graph.mark_dirty(node);
+            }
+        }
+        if let Some(graph) = &mut self.graph {
+            // The observers stay with the editor's graph, and are told that its
+            // contents were replaced.
+            std::mem::swap(graph, &mut state.graph);
+            std::mem::swap(&mut graph.observers, &mut state.graph.observers);
+            state.graph.notify(GraphEvent::Replaced);
+            // The restored graph may differ anywhere from the current one.
+            state.graph.mark_all_dirty();
+        }
+        if let Some(node_order) = &mut self.node_order {
+            std::mem::swap(node_order, &mut state.node_order);
+        }
+        if let Some(node_positions) = &mut self.node_positions {
+            std::mem::swap(node_positions, &mut state.node_positions);
+        }
+        if let Some((frames, frame_order)) = &mut self.frames {
+            std::mem::swap(frames, &mut state.frames);
+            std::mem::swap(frame_order, &mut state.frame_order);
+        }
+        if let Some(knots) = &mut self.knots {
+            std::mem::swap(knots, &mut state.knots);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HistoryEntry<NodeData, DataType, ValueType> {
+    pub step: HistoryStep,
+    /// The open groups when the step was made. The diff applies to the graph
+    /// that was open then.
+    pub open_groups: Vec<NodeId>,
+    /// The parts of the state changed by the step, as they are on the other
+    /// side of it: Before the step for undo entries, after it for redo
+    /// entries.
+    pub diff: EditorDiff<NodeData, DataType, ValueType>,
+}
+
+/// A linear undo / redo history, made of the parts of the editor state each
+/// step changed.
+///
+/// The memory used by the history is bounded by `max_steps` entries in each
+/// stack, plus one snapshot of the open graph. Each entry only stores the
+/// parts of the open graph its step changed: The node positions for a move,
+/// a single value for a value edit, and the whole open graph for structural
+/// edits like deleting a node.
+///
+/// The snapshot holds the current state of the open graph, which becomes the
+/// starting state of the next step. It is taken by
+/// [`GraphEditorState::sync_history`] when no interaction is ongoing, so
+/// continuous interactions only need a snapshot once they end.
+#[derive(Clone, Debug)]
+pub struct UndoHistory<NodeData, DataType, ValueType> {
+    /// Applied entries, oldest first.
+    pub undo_stack: Vec<HistoryEntry<NodeData, DataType, ValueType>>,
+    /// Undone entries, most recently undone last.
+    pub redo_stack: Vec<HistoryEntry<NodeData, DataType, ValueType>>,
+    /// The maximum number of steps that can be undone. Older entries are
+    /// discarded.
+    pub max_steps: usize,
+    /// While true, the recorded steps are merged with the last one, see
+    /// [`HistoryStep::coalesces_with`]. Reset by
+    /// [`UndoHistory::break_coalescing`].
+    pub coalescing: bool,
+    /// The state of the open graph before the last recorded step, or after
+    /// it once synced.
+    current: Option<EditorSnapshot<NodeData, DataType, ValueType>>,
+    /// Whether `current` holds the state after the last recorded step.
+    synced: bool,
+}
+
+impl<NodeData, DataType, ValueType> Default for UndoHistory<NodeData, DataType, ValueType> {
+    fn default() -> Self {
+        Self {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            max_steps: 100,
+            coalescing: false,
+            current: None,
+            synced: false,
+        }
+    }
+}
+
+impl<NodeData, DataType, ValueType> UndoHistory<NodeData, DataType, ValueType> {
+    /// Discards all entries. The history starts again from the state of the
+    /// editor the next time it is synced.
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.coalescing = false;
+        self.current = None;
+    }
+
+    /// Whether the history needs a new snapshot of the current state: The
+    /// state changed since the last snapshot and no interaction is ongoing,
+    /// or another graph than the one in the snapshot is open.
+    pub fn needs_snapshot(&self, open_groups: &[NodeId]) -> bool {
+        match &self.current {
+            Some(current) => {
+                current.open_groups != open_groups || (!self.synced && !self.coalescing)
+            }
+            None => true,
+        }
+    }
+
+    /// Stores the snapshot of the current state of the open graph, which is
+    /// the state the next recorded step will be undone to.
+    pub fn set_current(&mut self, snapshot: EditorSnapshot<NodeData, DataType, ValueType>) {
+        self.current = Some(snapshot);
+        self.synced = true;
+    }
+
+    /// Ends the current interaction. Once the history is synced, the next
+    /// recorded step will be stored as a separate entry even if it is of the
+    /// same kind as the last one.
+    pub fn break_coalescing(&mut self) {
+        self.coalescing = false;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// The step that would be reverted by the next call to `undo`.
+    pub fn undo_step(&self) -> Option<&HistoryStep> {
+        self.undo_stack.last().map(|entry| &entry.step)
+    }
+
+    /// The step that would be reapplied by the next call to `redo`.
+    pub fn redo_step(&self) -> Option<&HistoryStep> {
+        self.redo_stack.last().map(|entry| &entry.step)
+    }
+
+    /// Moves one step back in the history. `swap` must exchange the parts of
+    /// the editor stored in the entry with the current ones, so the entry
+    /// can be redone. Returns whether there was anything to undo.
+    pub fn undo(
+        &mut self,
+        swap: impl FnOnce(&mut HistoryEntry<NodeData, DataType, ValueType>),
+    ) -> bool {
+        let Some(mut entry) = self.undo_stack.pop() else {
+            return false;
+        };
+        swap(&mut entry);
+        self.redo_stack.push(entry);
+        self.coalescing = false;
+        self.current = None;
+        true
+    }
+
+    /// Moves one step forward in the history. `swap` must exchange the parts
+    /// of the editor stored in the entry with the current ones, so the entry
+    /// can be undone again. Returns whether there was anything to redo.
+    pub fn redo(
+        &mut self,
+        swap: impl FnOnce(&mut HistoryEntry<NodeData, DataType, ValueType>),
+    ) -> bool {
+        let Some(mut entry) = self.redo_stack.pop() else {
+            return false;
+        };
+        swap(&mut entry);
+        self.undo_stack.push(entry);
+        self.coalescing = false;
+        self.current = None;
+        true
+    }
+}
+
+impl<NodeData, DataType, ValueType> UndoHistory<NodeData, DataType, ValueType>
+where
+    NodeData: Clone,
+    DataType: Clone,
+    ValueType: Clone,
+{
+    /// Records a step, made since the current state was stored with
+    /// [`UndoHistory::set_current`]. This discards any redo entries.
+    ///
+    /// A step recorded before the history is synced again, because an
+    /// interaction is still ongoing, is merged with the last one: The two are
+    /// undone together.
+    pub fn push(&mut self, step: HistoryStep) {
+        self.redo_stack.clear();
+        self.coalescing = true;
+        let Some(current) = &self.current else {
+            // There's no known state to undo the step to.
+            return;
+        };
+        if self.synced {
+            let mut diff = EditorDiff::default();
+            diff.add_parts(&step, current);
+            self.undo_stack.push(HistoryEntry {
+                step,
+                open_groups: current.open_groups.clone(),
+                diff,
+            });
+            if self.undo_stack.len() > self.max_steps {
+                let excess = self.undo_stack.len() - self.max_steps;
+                self.undo_stack.drain(0..excess);
+            }
+            self.synced = false;
+        } else if let Some(last) = self.undo_stack.last_mut() {
+            last.diff.add_parts(&step, current);
+        }
+    }
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+where
+    NodeData: Clone,
+    DataType: Clone,
+    ValueType: Clone,
+{
+    /// Takes the snapshot of the current state the next step will be undone
+    /// to, when the history needs one, see [`UndoHistory::needs_snapshot`].
+    pub fn sync_history(&mut self) {
+        if self.history.needs_snapshot(&self.open_groups) {
+            let snapshot = self.with_open_group(|state| EditorSnapshot {
+                open_groups: state.open_groups.clone(),
+                graph: state.graph.clone(),
+                node_order: state.node_order.clone(),
+                node_positions: state.node_positions.clone(),
+                frames: state.frames.clone(),
+                frame_order: state.frame_order.clone(),
+                knots: state.knots.clone(),
+            });
+            self.history.set_current(snapshot);
+        }
+    }
+
+    /// Records the edits made since the history was last synced as an undo
+    /// step. [`GraphEditorState::update_history`] records the edits of the
+    /// editor. User code
+    /// modifying the state between two frames can call this afterwards, but
+    /// should prefer [`GraphEditorState::transaction`].
+    ///
+    /// Steps that don't coalesce are complete once recorded, so the current
+    /// state is synced right away. Coalescing steps are synced once their
+    /// interaction ends, see [`UndoHistory::break_coalescing`].
+    pub fn record_history(&mut self, step: HistoryStep) {
+        let complete = !step.coalesces_with(&step);
+        self.history.push(step);
+        if complete {
+            self.history.break_coalescing();
+            self.sync_history();
+        }
+    }
+
+    /// Applies `edit` to the state as a single undo step, and returns its
+    /// result. This is how user code pushes its own reversible operations:
+    /// Undoing the step restores the state from before `edit`, and redoing it
+    /// restores the state `edit` produced.
+    pub fn transaction<R>(&mut self, step: HistoryStep, edit: impl FnOnce(&mut Self) -> R) -> R {
+        self.history.break_coalescing();
+        self.sync_history();
+        let result = edit(self);
+        self.record_history(step);
+        self.history.break_coalescing();
+        result
+    }
+
+    /// Reverts the last recorded step. The graph the step was made in is
+    /// opened. Returns whether there was anything to undo.
+    pub fn undo(&mut self) -> bool {
+        let mut history = std::mem::take(&mut self.history);
+        let undone = history.undo(|entry| self.swap_history_entry(entry));
+        self.history = history;
+        self.sync_history();
+        undone
+    }
+
+    /// Reapplies the last undone step. The graph the step was made in is
+    /// opened. Returns whether there was anything to redo.
+    pub fn redo(&mut self) -> bool {
+        let mut history = std::mem::take(&mut self.history);
+        let redone = history.redo(|entry| self.swap_history_entry(entry));
+        self.history = history;
+        self.sync_history();
+        redone
+    }
+
+    /// Discards the undo history. Call this after replacing the graph, e.g.
+    /// when loading a new file.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Exchanges the parts of the state stored in `entry` with the current
+    /// ones, and clears any transient interaction referring to the old state.
+    fn swap_history_entry(&mut self, entry: &mut HistoryEntry<NodeData, DataType, ValueType>) {
+        self.open_groups = entry.open_groups.clone();
+        self.with_open_group(|state| entry.diff.swap(state));
+
+        self.validate_open_groups();
+        let graph = self.open_graph();
+        let selected_nodes = self
+            .selected_nodes
+            .iter()
+            .copied()
+            .filter(|id| graph.nodes.contains_key(*id))
+            .collect();
+        self.selected_nodes = selected_nodes;
+        self.connection_in_progress = None;
+        self.ongoing_box_selection = None;
+    }
+}
+impl<NodeData, DataType, ValueType, NodeTemplate, UserResponse, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+where
+    NodeData: NodeDataTrait<
+            Response = UserResponse,
+            UserState = UserState,
+            DataType = DataType,
+            ValueType = ValueType,
+        > + Clone,
+    UserResponse: UserResponseTrait,
+    DataType: Clone,
+    ValueType: Clone,
+{
+    /// Records the edits made by the editor during the frame of `response` as
+    /// an undo step, and handles the undo / redo shortcuts. Call it after
+    /// [`GraphEditorState::draw_graph_editor`], with the same `ui`, to make
+    /// the edits of the user undoable.
+    ///
+    /// An interaction, whose edits are merged in a single undo step, goes on
+    /// while the mouse is down or while the same widget has keyboard focus.
+    /// Once it ends, the history takes a snapshot of the state for the next
+    /// step to go back to.
+    pub fn update_history(
+        &mut self,
+        ui: &egui::Ui,
+        response: &GraphResponse<UserResponse, NodeData>,
+    ) {
+        let pointer_down = ui.ctx().input(|i| i.pointer.any_down());
+        let (focus, last_focus) = ui.ctx().memory_mut(|mem| {
+            let focus = mem.focus();
+            let focus_id = ui.id().with("history_focus");
+            let last_focus = mem.data.get_temp::<Option<egui::Id>>(focus_id).flatten();
+            mem.data.insert_temp(focus_id, focus);
+            (focus, last_focus)
+        });
+        if focus != last_focus {
+            self.history.break_coalescing();
+        }
+
+        // A discrete edit, like bypassing a node, takes precedence over a
+        // continuous one, so it isn't merged into an ongoing drag.
+        let steps: Vec<HistoryStep> = response
+            .node_responses
+            .iter()
+            .filter_map(HistoryStep::from_response)
+            .collect();
+        if let Some(step) = steps
+            .iter()
+            .find(|step| !step.coalesces_with(step))
+            .or(steps.first())
+        {
+            self.record_history(step.clone());
+        }
+
+        if !pointer_down && focus.is_none() {
+            self.history.break_coalescing();
+        }
+        self.sync_history();
+
+        // Undo / redo shortcuts. These are ignored while a widget has keyboard
+        // focus, so text fields can keep their own undo behavior.
+        if response.cursor_in_editor && focus.is_none() {
+            let (undo, redo) = ui.ctx().input_mut(|i| {
+                let redo = i.consume_key(
+                    egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+                    egui::Key::Z,
+                ) || i.consume_key(egui::Modifiers::COMMAND, egui::Key::Y);
+                let undo = i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z);
+                (undo, redo)
+            });
+            if undo {
+                self.undo();
+            } else if redo {
+                self.redo();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestState = GraphEditorState<(), (), (), (), ()>;
+
+    fn add_node(state: &mut TestState) -> NodeId {
+        let node_id = state.graph.add_node("node".into(), (), |_, _| {});
+        state.node_order.push(node_id);
+        state.node_positions.insert(node_id, egui::Pos2::ZERO);
+        node_id
+    }
+
+    fn move_node(state: &mut TestState, node_id: NodeId) {
+        state.node_positions[node_id] += egui::vec2(10.0, 0.0);
+        state.record_history(HistoryStep::MoveNodes);
+    }
+
+    #[test]
+    pub fn test_history() {
+        let mut state = TestState::default();
+        assert!(!state.history.can_undo());
+        assert!(!state.undo());
+
+        // Pushing a step records the state before it
+        state.sync_history();
+        let node_id = state.transaction(HistoryStep::CreateNode, add_node);
+        assert_eq!(state.history.undo_step(), Some(&HistoryStep::CreateNode));
+        let diff = &state.history.undo_stack[0].diff;
+        assert_eq!(diff.graph.as_ref().unwrap().nodes.len(), 0);
+
+        // Consecutive moves are coalesced while the interaction lasts, and the
+        // state is only snapshotted when it ends.
+        for _ in 0..3 {
+            state.sync_history();
+            move_node(&mut state, node_id);
+            assert!(!state.history.needs_snapshot(&state.open_groups));
+        }
+        state.history.break_coalescing();
+        assert!(state.history.needs_snapshot(&state.open_groups));
+        state.sync_history();
+        move_node(&mut state, node_id);
+        state.history.break_coalescing();
+        state.sync_history();
+        assert_eq!(state.history.undo_stack.len(), 3);
+        assert_eq!(state.node_positions[node_id].x, 40.0);
+
+        // Moves only store the node positions
+        let diff = &state.history.undo_stack[1].diff;
+        assert!(diff.graph.is_none() && diff.node_order.is_none());
+        assert!(diff.node_positions.is_some());
+
+        // Undo goes back one interaction at a time
+        assert!(state.undo());
+        assert_eq!(state.node_positions[node_id].x, 30.0);
+        assert!(state.undo());
+        assert_eq!(state.node_positions[node_id].x, 0.0);
+        assert!(state.undo());
+        assert!(state.graph.nodes.is_empty());
+        assert!(!state.undo());
+
+        // Redo reapplies the steps in order
+        assert_eq!(state.history.redo_step(), Some(&HistoryStep::CreateNode));
+        assert!(state.redo());
+        assert!(state.graph.nodes.contains_key(node_id));
+        assert!(state.redo());
+        assert_eq!(state.node_positions[node_id].x, 30.0);
+
+        // A new step discards the steps left to redo
+        assert!(state.history.can_redo());
+        state.sync_history();
+        state.transaction(HistoryStep::User("Delete".into()), |state| {
+            state.graph.remove_node(node_id)
+        });
+        assert!(!state.history.can_redo());
+        assert!(!state.redo());
+        assert!(state.undo());
+        assert_eq!(state.node_positions[node_id].x, 30.0);
+        assert!(state.graph.nodes.contains_key(node_id));
+    }
+
+    #[test]
+    pub fn test_history_max_steps() {
+        let mut state = TestState::default();
+        state.history.max_steps = 2;
+        let nodes: Vec<_> = (0..3)
+            .map(|_| state.transaction(HistoryStep::CreateNode, add_node))
+            .collect();
+        assert_eq!(state.history.undo_stack.len(), 2);
+
+        // The oldest step was evicted, so the first node can't be undone
+        assert!(state.undo());
+        assert!(state.undo());
+        assert!(!state.undo());
+        assert_eq!(state.graph.nodes.len(), 1);
+        assert!(state.graph.nodes.contains_key(nodes[0]));
+    }
+
+    #[test]
+    pub fn test_history_in_group() {
+        let mut state = GraphEditorState::<(), (), f32, (), ()>::default();
+        let node_id = state.graph.add_node("node".into(), (), |graph, node_id| {
+            graph.add_input_param(
+                node_id,
+                "x".into(),
+                (),
+                1.0,
+                InputParamKind::ConstantOnly,
+                true,
+            );
+        });
+        let group_id = state.group_nodes(&[node_id], "group".into(), ());
+        state.open_group(group_id);
+        let input = state.open_graph().inputs.keys().next().unwrap();
+
+        // A value edit only stores the edited value
+        state.transaction(HistoryStep::EditValue(input), |state| {
+            state.with_open_group(|state| state.graph[input].value = 2.0)
+        });
+        let diff = &state.history.undo_stack[0].diff;
+        assert!(diff.graph.is_none());
+        assert_eq!(diff.values, vec![(input, 1.0)]);
+
+        // Undoing it goes back to the group it was made in
+        state.close_group();
+        assert!(state.undo());
+        assert_eq!(state.open_groups, vec![group_id]);
+        assert_eq!(state.open_graph()[input].value, 1.0);
+        assert!(state.redo());
+        assert_eq!(state.open_graph()[input].value, 2.0);
+    }
+}
@@ -0,0 +1,711 @@
+use super::*;
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "persistence")]
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// A graph in the interchange file format. Unlike the serialized [`Graph`],
+/// which mirrors its in-memory storage, this format only depends on the
+/// contents of the graph: Nodes have small numeric ids, parameters are
+/// referred to by name, and connections are listed as pairs of parameters.
+/// This makes files readable, editable by hand and friendly to diffs, and
+/// allows other tools to generate them.
+///
+/// Use [`GraphEditorState::to_file`] and [`GraphEditorState::from_file`] to
+/// convert editor states. With the `persistence` feature, files are written
+/// as text with [`GraphFile::to_text`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct GraphFile<NodeData, DataType, ValueType> {
+    /// The nodes of the graph, sorted by id.
+    pub nodes: Vec<FileNode<NodeData, DataType, ValueType>>,
+    /// The connections of the graph. The connections of each input are listed
+    /// in connection order.
+    pub connections: Vec<FileConnection>,
+    /// The ids of the nodes, in draw order.
+    pub node_order: Vec<u32>,
+    /// The comment frames of the graph, in draw order.
+    #[cfg_attr(
+        feature = "persistence",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub frames: Vec<CommentFrame>,
+}
+
+/// A node of a [`GraphFile`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct FileNode<NodeData, DataType, ValueType> {
+    /// The id of the node, unique in its file. A graph that is loaded and
+    /// saved again keeps its ids, and nodes added in between are numbered
+    /// after the largest id of the file. See [`GraphEditorState::file_ids`].
+    pub id: u32,
+    pub label: String,
+    pub position: egui::Pos2,
+    pub user_data: NodeData,
+    pub inputs: Vec<FileInput<DataType, ValueType>>,
+    pub outputs: Vec<FileOutput<DataType>>,
+    #[cfg_attr(
+        feature = "persistence",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub bypassed: bool,
+    /// The contents of group nodes.
+    #[cfg_attr(
+        feature = "persistence",
+        serde(default = "Option::default", skip_serializing_if = "Option::is_none")
+    )]
+    pub group: Option<FileGroup<NodeData, DataType, ValueType>>,
+}
+
+/// An input parameter of a [`FileNode`]. See [`InputParam`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct FileInput<DataType, ValueType> {
+    pub name: String,
+    pub typ: DataType,
+    pub value: ValueType,
+    #[cfg_attr(feature = "persistence", serde(default = "default_input_kind"))]
+    pub kind: InputParamKind,
+    #[cfg_attr(feature = "persistence", serde(default = "default_shown_inline"))]
+    pub shown_inline: bool,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub cardinality: InputCardinality,
+    #[cfg_attr(
+        feature = "persistence",
+        serde(default = "Option::default", skip_serializing_if = "Option::is_none")
+    )]
+    pub wildcard: Option<Wildcard<DataType>>,
+    #[cfg_attr(
+        feature = "persistence",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub variadic: Option<String>,
+}
+
+#[cfg(feature = "persistence")]
+fn default_input_kind() -> InputParamKind {
+    InputParamKind::ConnectionOrConstant
+}
+
+#[cfg(feature = "persistence")]
+fn default_shown_inline() -> bool {
+    true
+}
+
+/// An output parameter of a [`FileNode`]. See [`OutputParam`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct FileOutput<DataType> {
+    pub name: String,
+    pub typ: DataType,
+    #[cfg_attr(
+        feature = "persistence",
+        serde(default = "Option::default", skip_serializing_if = "Option::is_none")
+    )]
+    pub wildcard: Option<Wildcard<DataType>>,
+}
+
+/// A parameter of a [`GraphFile`]: The id of its node, and its name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct FileParam {
+    pub node: u32,
+    pub param: String,
+}
+
+/// A connection of a [`GraphFile`], from an output to an input.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct FileConnection {
+    pub from: FileParam,
+    pub to: FileParam,
+    /// The knots the wire is rerouted through, from the output on. See
+    /// [`WireKnots`].
+    #[cfg_attr(
+        feature = "persistence",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub knots: Vec<egui::Pos2>,
+}
+
+/// The contents of a group node in a [`GraphFile`]. See [`NodeGroup`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct FileGroup<NodeData, DataType, ValueType> {
+    /// The nested graph. Its node ids are independent from the ids of the
+    /// enclosing graph.
+    pub graph: GraphFile<NodeData, DataType, ValueType>,
+    /// The inputs of the nested graph fed by each input of the group node.
+    pub inputs: Vec<(String, Vec<FileParam>)>,
+    /// The output of the nested graph forwarded by each output of the group
+    /// node.
+    pub outputs: Vec<(String, FileParam)>,
+}
+
+#[cfg(feature = "persistence")]
+impl<NodeData, DataType, ValueType> GraphFile<NodeData, DataType, ValueType>
+where
+    NodeData: Serialize + DeserializeOwned,
+    DataType: Serialize + DeserializeOwned,
+    ValueType: Serialize + DeserializeOwned,
+{
+    /// Writes the file as pretty-printed text, with one field per line.
+    pub fn to_text(&self) -> Result<String, EguiGraphError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| EguiGraphError::Persistence(err.to_string()))
+    }
+
+    /// Parses a file written by [`GraphFile::to_text`], or by other tools.
+    pub fn from_text(text: &str) -> Result<Self, EguiGraphError> {
+        ron::from_str(text).map_err(|err| EguiGraphError::Persistence(err.to_string()))
+    }
+}
+
+/// Lists the frames of a [`GraphFile`], in draw order.
+fn frames_to_file(
+    frames: &SlotMap<FrameId, CommentFrame>,
+    frame_order: &[FrameId],
+) -> Vec<CommentFrame> {
+    frame_order
+        .iter()
+        .filter_map(|frame| frames.get(*frame).cloned())
+        .collect()
+}
+
+/// Creates the frames of a [`GraphFile`], and their draw order.
+fn frames_from_file(
+    file_frames: Vec<CommentFrame>,
+) -> (SlotMap<FrameId, CommentFrame>, Vec<FrameId>) {
+    let mut frames = SlotMap::with_key();
+    let frame_order = file_frames
+        .into_iter()
+        .map(|frame| frames.insert(frame))
+        .collect();
+    (frames, frame_order)
+}
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
+    /// The ids of the nodes in a [`GraphFile`]: Nodes keep their id in
+    /// `known`, and the other nodes are numbered after the largest known id,
+    /// in the order they are stored in the graph.
+    pub(crate) fn file_ids(&self, known: &SecondaryMap<NodeId, u32>) -> SecondaryMap<NodeId, u32> {
+        let mut next_id = known.values().max().map_or(0, |id| id + 1);
+        let mut used = HashSet::new();
+        let mut file_ids = SecondaryMap::new();
+        for node in self.iter_nodes() {
+            let id = match known.get(node) {
+                Some(id) if used.insert(*id) => *id,
+                _ => {
+                    next_id += 1;
+                    next_id - 1
+                }
+            };
+            file_ids.insert(node, id);
+        }
+        file_ids
+    }
+}
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType>
+where
+    NodeData: Clone,
+    DataType: Clone,
+    ValueType: Clone,
+{
+    /// Converts the graph to the interchange format, with the given draw
+    /// order, positions, knots and known file ids, see [`Graph::file_ids`].
+    /// The frames of the file are left empty.
+    /// Fails if a node has two inputs, or two outputs, with the same name,
+    /// since connections refer to parameters by name.
+    fn to_file(
+        &self,
+        node_order: &[NodeId],
+        node_positions: &SecondaryMap<NodeId, egui::Pos2>,
+        knots: &WireKnots,
+        known_ids: &SecondaryMap<NodeId, u32>,
+    ) -> Result<GraphFile<NodeData, DataType, ValueType>, EguiGraphError> {
+        let file_ids = self.file_ids(known_ids);
+        let file_param = |node: NodeId, name: &str| FileParam {
+            node: file_ids[node],
+            param: name.to_string(),
+        };
+
+        let mut nodes = Vec::with_capacity(file_ids.len());
+        let mut connections = Vec::new();
+        for node_id in self.iter_nodes() {
+            let node = &self[node_id];
+            let mut names = HashSet::new();
+            let mut inputs = Vec::with_capacity(node.inputs.len());
+            for (name, input_id) in &node.inputs {
+                if !names.insert(name) {
+                    return Err(EguiGraphError::DuplicateParameterName(
+                        node_id,
+                        name.clone(),
+                    ));
+                }
+                let input = &self[*input_id];
+                inputs.push(FileInput {
+                    name: name.clone(),
+                    typ: input.typ.clone(),
+                    value: input.value.clone(),
+                    kind: input.kind,
+                    shown_inline: input.shown_inline,
+                    cardinality: input.cardinality,
+                    wildcard: input.wildcard.clone(),
+                    variadic: input.variadic.clone(),
+                });
+                for output in self.input_connections(*input_id) {
+                    connections.push(FileConnection {
+                        from: self.output_file_param(&file_ids, *output)?,
+                        to: file_param(node_id, name),
+                        knots: knots.get(*output, *input_id).to_vec(),
+                    });
+                }
+            }
+
+            let mut names = HashSet::new();
+            let mut outputs = Vec::with_capacity(node.outputs.len());
+            for (name, output_id) in &node.outputs {
+                if !names.insert(name) {
+                    return Err(EguiGraphError::DuplicateParameterName(
+                        node_id,
+                        name.clone(),
+                    ));
+                }
+                let output = &self[*output_id];
+                outputs.push(FileOutput {
+                    name: name.clone(),
+                    typ: output.typ.clone(),
+                    wildcard: output.wildcard.clone(),
+                });
+            }
+
+            let group = match self.groups.get(node_id) {
+                Some(group) => Some(self.group_to_file(node_id, group)?),
+                None => None,
+            };
+
+            nodes.push(FileNode {
+                id: file_ids[node_id],
+                label: node.label.clone(),
+                position: node_positions
+                    .get(node_id)
+                    .copied()
+                    .unwrap_or(egui::Pos2::ZERO),
+                user_data: node.user_data.clone(),
+                inputs,
+                outputs,
+                bypassed: node.bypassed,
+                group,
+            });
+        }
+
+        Ok(GraphFile {
+            nodes,
+            connections,
+            node_order: node_order.iter().map(|node| file_ids[*node]).collect(),
+            frames: Vec::new(),
+        })
+    }
+
+    fn group_to_file(
+        &self,
+        node_id: NodeId,
+        group: &NodeGroup<NodeData, DataType, ValueType>,
+    ) -> Result<FileGroup<NodeData, DataType, ValueType>, EguiGraphError> {
+        let inner = &group.graph;
+        let inner_ids = inner.file_ids(&group.file_ids);
+        let mut inputs = Vec::new();
+        for (name, input) in &self[node_id].inputs {
+            if let Some(inner_inputs) = group.inputs.get(*input) {
+                let inner_inputs = inner_inputs
+                    .iter()
+                    .filter(|input| inner.inputs.contains_key(**input))
+                    .map(|input| inner.input_file_param(&inner_ids, *input))
+                    .collect::<Result<_, _>>()?;
+                inputs.push((name.clone(), inner_inputs));
+            }
+        }
+        let mut outputs = Vec::new();
+        for (name, output) in &self[node_id].outputs {
+            if let Some(inner_output) = group.outputs.get(*output) {
+                if inner.outputs.contains_key(*inner_output) {
+                    outputs.push((
+                        name.clone(),
+                        inner.output_file_param(&inner_ids, *inner_output)?,
+                    ));
+                }
+            }
+        }
+
+        let mut graph = inner.to_file(
+            &group.node_order,
+            &group.node_positions,
+            &group.knots,
+            &group.file_ids,
+        )?;
+        graph.frames = frames_to_file(&group.frames, &group.frame_order);
+        Ok(FileGroup {
+            graph,
+            inputs,
+            outputs,
+        })
+    }
+
+    /// Converts a graph from the interchange format. Returns the graph, its
+    /// draw order, positions and knots, and the file id of each node. The
+    /// frames of the file are ignored, but not those of its groups.
+    ///
+    /// Fails if the draw order of the file does not list each node exactly
+    /// once.
+    #[allow(clippy::type_complexity)]
+    fn from_file(
+        file: GraphFile<NodeData, DataType, ValueType>,
+    ) -> Result<
+        (
+            Self,
+            Vec<NodeId>,
+            SecondaryMap<NodeId, egui::Pos2>,
+            WireKnots,
+            SecondaryMap<NodeId, u32>,
+        ),
+        EguiGraphError,
+    > {
+        let mut graph = Graph::new();
+        let mut node_positions = SecondaryMap::new();
+        let mut node_ids = HashMap::new();
+        let mut knots = WireKnots::default();
+        let mut groups = Vec::new();
+        let mut file_nodes = file.nodes;
+        file_nodes.sort_by_key(|node| node.id);
+        for node in file_nodes {
+            if node_ids.contains_key(&node.id) {
+                return Err(EguiGraphError::InvalidFileNode(node.id));
+            }
+            let node_id = graph.add_node(node.label, node.user_data, |_, _| {});
+            for input in node.inputs {
+                let param = InputParam {
+                    id: InputId::default(),
+                    typ: input.typ,
+                    value: input.value,
+                    kind: input.kind,
+                    node: node_id,
+                    shown_inline: input.shown_inline,
+                    cardinality: input.cardinality,
+                    wildcard: input.wildcard,
+                    variadic: input.variadic,
+                };
+                graph.add_input_param_like(node_id, input.name, param);
+            }
+            for output in node.outputs {
+                let param = OutputParam {
+                    id: OutputId::default(),
+                    node: node_id,
+                    typ: output.typ,
+                    wildcard: output.wildcard,
+                };
+                graph.add_output_param_like(node_id, output.name, param);
+            }
+            graph.set_bypassed(node_id, node.bypassed);
+            node_ids.insert(node.id, node_id);
+            node_positions.insert(node_id, node.position);
+            if let Some(group) = node.group {
+                groups.push((node_id, group));
+            }
+        }
+
+        let node = |id: u32| {
+            node_ids
+                .get(&id)
+                .copied()
+                .ok_or(EguiGraphError::InvalidFileNode(id))
+        };
+        for connection in file.connections {
+            let output = graph[node(connection.from.node)?].get_output(&connection.from.param)?;
+            let input = graph[node(connection.to.node)?].get_input(&connection.to.param)?;
+            graph.add_connection(output, input);
+            knots.set(output, input, connection.knots);
+        }
+        let mut ordered = HashSet::new();
+        let mut node_order = Vec::with_capacity(file.node_order.len());
+        for id in file.node_order {
+            if !ordered.insert(id) {
+                return Err(EguiGraphError::InvalidFileNode(id));
+            }
+            node_order.push(node(id)?);
+        }
+        if let Some(id) = node_ids.keys().find(|id| !ordered.contains(id)) {
+            return Err(EguiGraphError::UnorderedFileNode(*id));
+        }
+
+        for (node_id, mut group) in groups {
+            let (frames, frame_order) = frames_from_file(std::mem::take(&mut group.graph.frames));
+            let (inner, inner_order, inner_positions, inner_knots, inner_ids) =
+                Self::from_file(group.graph)?;
+            let inner_nodes: HashMap<u32, NodeId> =
+                inner_ids.iter().map(|(node, id)| (*id, node)).collect();
+            let inner_node = |id: u32| {
+                inner_nodes
+                    .get(&id)
+                    .copied()
+                    .ok_or(EguiGraphError::InvalidFileNode(id))
+            };
+            let mut inputs = SecondaryMap::new();
+            for (name, inner_inputs) in group.inputs {
+                let input = graph[node_id].get_input(&name)?;
+                let inner_inputs = inner_inputs
+                    .into_iter()
+                    .map(|param| inner[inner_node(param.node)?].get_input(&param.param))
+                    .collect::<Result<_, _>>()?;
+                inputs.insert(input, inner_inputs);
+            }
+            let mut outputs = SecondaryMap::new();
+            for (name, param) in group.outputs {
+                let output = graph[node_id].get_output(&name)?;
+                outputs.insert(
+                    output,
+                    inner[inner_node(param.node)?].get_output(&param.param)?,
+                );
+            }
+            graph.groups.insert(
+                node_id,
+                NodeGroup {
+                    graph: inner,
+                    node_order: inner_order,
+                    node_positions: inner_positions,
+                    frames,
+                    frame_order,
+                    knots: inner_knots,
+                    file_ids: inner_ids,
+                    inputs,
+                    outputs,
+                },
+            );
+        }
+
+        let file_ids = node_ids.into_iter().map(|(id, node)| (node, id)).collect();
+        Ok((graph, node_order, node_positions, knots, file_ids))
+    }
+
+    fn input_file_param(
+        &self,
+        file_ids: &SecondaryMap<NodeId, u32>,
+        input: InputId,
+    ) -> Result<FileParam, EguiGraphError> {
+        let node = self[input].node;
+        self[node]
+            .inputs
+            .iter()
+            .find(|(_, id)| *id == input)
+            .map(|(name, _)| FileParam {
+                node: file_ids[node],
+                param: name.clone(),
+            })
+            .ok_or(EguiGraphError::InvalidParameterId(input.into()))
+    }
+
+    fn output_file_param(
+        &self,
+        file_ids: &SecondaryMap<NodeId, u32>,
+        output: OutputId,
+    ) -> Result<FileParam, EguiGraphError> {
+        let node = self[output].node;
+        self[node]
+            .outputs
+            .iter()
+            .find(|(_, id)| *id == output)
+            .map(|(name, _)| FileParam {
+                node: file_ids[node],
+                param: name.clone(),
+            })
+            .ok_or(EguiGraphError::InvalidParameterId(output.into()))
+    }
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+where
+    NodeData: Clone,
+    DataType: Clone,
+    ValueType: Clone,
+{
+    /// Converts the graph of the editor, with its draw order, node positions,
+    /// frames, knots and the contents of its group nodes, to the interchange format.
+    /// See [`GraphFile`]. Nodes loaded from a file keep their id in
+    /// [`GraphEditorState::file_ids`], the other nodes get fresh ids.
+    ///
+    /// Fails if a node has two inputs, or two outputs, with the same name,
+    /// since connections refer to parameters by name.
+    pub fn to_file(&self) -> Result<GraphFile<NodeData, DataType, ValueType>, EguiGraphError> {
+        let mut file = self.graph.to_file(
+            &self.node_order,
+            &self.node_positions,
+            &self.knots,
+            &self.file_ids,
+        )?;
+        file.frames = frames_to_file(&self.frames, &self.frame_order);
+        Ok(file)
+    }
+
+    /// Creates an editor state from a graph in the interchange format. This is
+    /// the inverse of [`GraphEditorState::to_file`]. The rest of the editor
+    /// state, like the pan and zoom, is set to its default.
+    ///
+    /// Fails if the file refers to missing nodes or parameters, or if its draw
+    /// order does not list each node exactly once.
+    pub fn from_file(
+        mut file: GraphFile<NodeData, DataType, ValueType>,
+    ) -> Result<Self, EguiGraphError> {
+        let (frames, frame_order) = frames_from_file(std::mem::take(&mut file.frames));
+        let (graph, node_order, node_positions, knots, file_ids) = Graph::from_file(file)?;
+        Ok(Self {
+            graph,
+            node_order,
+            node_positions,
+            frames,
+            frame_order,
+            knots,
+            file_ids,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestState = GraphEditorState<u32, (), f32, (), ()>;
+
+    fn add_node(state: &mut TestState, data: u32, pos: egui::Pos2) -> NodeId {
+        let node = state.graph.add_node("node".into(), data, |graph, node_id| {
+            graph.add_input_param(
+                node_id,
+                "in".into(),
+                (),
+                data as f32,
+                InputParamKind::ConnectionOrConstant,
+                true,
+            );
+            graph.add_output_param(node_id, "out".into(), ());
+        });
+        state.node_order.push(node);
+        state.node_positions.insert(node, pos);
+        node
+    }
+
+    #[test]
+    pub fn test_file_round_trip() {
+        let mut state = TestState::default();
+        let a = add_node(&mut state, 1, egui::pos2(0.0, 0.0));
+        let b = add_node(&mut state, 2, egui::pos2(100.0, 0.0));
+        let c = add_node(&mut state, 3, egui::pos2(200.0, 0.0));
+        let d = add_node(&mut state, 4, egui::pos2(300.0, 0.0));
+        for (from, to) in [(a, b), (b, c), (c, d)] {
+            let output = state.graph[from].get_output("out").unwrap();
+            let input = state.graph[to].get_input("in").unwrap();
+            state.graph.add_connection(output, input);
+        }
+        state.group_nodes(&[b, c], "group".into(), 0);
+        let output = state.graph[a].get_output("out").unwrap();
+        let input = state.graph.outgoing(output)[0];
+        state.knots.insert(output, input, 0, egui::pos2(50.0, 50.0));
+        state.add_frame(CommentFrame::new(
+            "frame",
+            egui::pos2(-10.0, -10.0),
+            egui::vec2(500.0, 200.0),
+        ));
+
+        let file = state.to_file().unwrap();
+        assert_eq!(file.nodes.len(), 3);
+        assert_eq!(file.connections.len(), 2);
+        assert_eq!(file.frames.len(), 1);
+        assert_eq!(file.connections[0].knots, vec![egui::pos2(50.0, 50.0)]);
+        let group = file.nodes.iter().find_map(|node| node.group.as_ref());
+        assert_eq!(group.unwrap().graph.connections.len(), 1);
+
+        // Converting the imported state back yields the same file
+        let imported = TestState::from_file(file.clone()).unwrap();
+        assert_eq!(imported.to_file().unwrap(), file);
+
+        #[cfg(feature = "persistence")]
+        assert_eq!(
+            GraphFile::from_text(&file.to_text().unwrap()).unwrap(),
+            file
+        );
+
+        let mut broken = file;
+        broken.connections[0].from.param = "missing".into();
+        assert!(TestState::from_file(broken).is_err());
+    }
+
+    #[test]
+    pub fn test_file_ids() {
+        let mut state = TestState::default();
+        let a = add_node(&mut state, 1, egui::pos2(0.0, 0.0));
+        let b = add_node(&mut state, 2, egui::pos2(100.0, 0.0));
+        let output = state.graph[a].get_output("out").unwrap();
+        let input = state.graph[b].get_input("in").unwrap();
+        state.graph.add_connection(output, input);
+        let mut file = state.to_file().unwrap();
+        assert_eq!(file.node_order, vec![0, 1]);
+
+        // Files written by other tools may use any ids. Loaded nodes keep
+        // their id when saved again, and new nodes are numbered after them.
+        for node in &mut file.nodes {
+            node.id = node.id * 1_000_000 + 7;
+        }
+        file.node_order = vec![1_000_007, 7];
+        file.connections[0].from.node = 7;
+        file.connections[0].to.node = 1_000_007;
+        let mut imported = TestState::from_file(file.clone()).unwrap();
+        assert_eq!(imported.graph.nodes.len(), 2);
+        assert_eq!(imported.to_file().unwrap(), file);
+        add_node(&mut imported, 3, egui::pos2(200.0, 0.0));
+        assert_eq!(
+            imported.to_file().unwrap().node_order,
+            vec![1_000_007, 7, 1_000_008]
+        );
+
+        let mut repeated = file.clone();
+        repeated.nodes[1].id = 7;
+        assert!(TestState::from_file(repeated).is_err());
+
+        // The draw order must list each node exactly once
+        let mut missing = file.clone();
+        missing.node_order = vec![7];
+        assert!(matches!(
+            TestState::from_file(missing),
+            Err(EguiGraphError::UnorderedFileNode(1_000_007))
+        ));
+        let mut duplicated = file;
+        duplicated.node_order = vec![7, 1_000_007, 7];
+        assert!(matches!(
+            TestState::from_file(duplicated),
+            Err(EguiGraphError::InvalidFileNode(7))
+        ));
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    pub fn test_file_defaults() {
+        let text = r#"(
+            nodes: [(
+                id: 0,
+                label: "node",
+                position: (x: 0.0, y: 0.0),
+                user_data: 1,
+                inputs: [(name: "in", typ: (), value: 1.0)],
+                outputs: [],
+            )],
+            connections: [],
+            node_order: [0],
+        )"#;
+        let file = GraphFile::<u32, (), f32>::from_text(text).unwrap();
+        let input = &file.nodes[0].inputs[0];
+        assert_eq!(input.kind, InputParamKind::ConnectionOrConstant);
+        assert!(input.shown_inline);
+        assert_eq!(input.cardinality, InputCardinality::Single);
+    }
+}
@@ -0,0 +1,332 @@
+use super::*;
+use egui::epaint::CubicBezierShape;
+use egui::{Color32, Id, Pos2, Rect, Sense, Stroke, Vec2};
+
+#[cfg(feature = "persistence")]
+use serde::{Deserialize, Serialize};
+
+/// The radius of the handle drawn on each knot.
+const KNOT_RADIUS: f32 = 5.0;
+/// Double clicking closer than this to a wire inserts a knot in it.
+const DISTANCE_TO_WIRE: f32 = 6.0;
+
+/// The reroute points, or knots, of the wires of a graph. A wire goes from its
+/// output through each of its knots, in order, to its input, which allows
+/// steering long wires around the nodes.
+///
+/// Knots only change how connections are drawn. They don't belong to the
+/// [`Graph`], they are stored in the [`GraphEditorState`] next to the node
+/// positions, so evaluators walking the connections of the graph never see
+/// them and the wire keeps the data type of its output.
+///
+/// Knots are inserted by double clicking a wire, moved by dragging them, and
+/// removed by double clicking them. The knots of a wire are discarded when
+/// its connection is removed.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct WireKnots {
+    /// The knots of the wires ending at each input, per connected output.
+    /// Knot positions are in the same coordinates as the node positions.
+    wires: SecondaryMap<InputId, Vec<(OutputId, Vec<Pos2>)>>,
+}
+
+impl WireKnots {
+    pub fn is_empty(&self) -> bool {
+        self.wires.is_empty()
+    }
+
+    /// The knots of the wire from `output` to `input`, starting from the
+    /// output. Empty when the wire has no knots.
+    pub fn get(&self, output: OutputId, input: InputId) -> &[Pos2] {
+        self.wires
+            .get(input)
+            .and_then(|wires| wires.iter().find(|(id, _)| *id == output))
+            .map_or(&[], |(_, knots)| knots.as_slice())
+    }
+
+    pub fn get_mut(&mut self, output: OutputId, input: InputId) -> Option<&mut Vec<Pos2>> {
+        self.wires
+            .get_mut(input)?
+            .iter_mut()
+            .find(|(id, _)| *id == output)
+            .map(|(_, knots)| knots)
+    }
+
+    /// Replaces the knots of the wire from `output` to `input`.
+    pub fn set(&mut self, output: OutputId, input: InputId, knots: Vec<Pos2>) {
+        self.clear(output, input);
+        if knots.is_empty() {
+            return;
+        }
+        if let Some(entry) = self.wires.entry(input) {
+            entry.or_default().push((output, knots));
+        }
+    }
+
+    /// Inserts a knot at `index` in the wire from `output` to `input`. The
+    /// knot is appended when `index` is past the last knot of the wire.
+    pub fn insert(&mut self, output: OutputId, input: InputId, index: usize, position: Pos2) {
+        match self.get_mut(output, input) {
+            Some(knots) => knots.insert(index.min(knots.len()), position),
+            None => self.set(output, input, vec![position]),
+        }
+    }
+
+    /// Removes the knot at `index` of the wire from `output` to `input`.
+    pub fn remove(&mut self, output: OutputId, input: InputId, index: usize) -> Option<Pos2> {
+        let knots = self.get_mut(output, input)?;
+        if index >= knots.len() {
+            return None;
+        }
+        let knot = knots.remove(index);
+        if knots.is_empty() {
+            self.clear(output, input);
+        }
+        Some(knot)
+    }
+
+    /// Removes all the knots of the wire from `output` to `input`.
+    pub fn clear(&mut self, output: OutputId, input: InputId) {
+        if let Some(wires) = self.wires.get_mut(input) {
+            wires.retain(|(id, _)| *id != output);
+            if wires.is_empty() {
+                self.wires.remove(input);
+            }
+        }
+    }
+
+    /// Iterates the wires with knots, as `(output, input, knots)`.
+    pub fn iter(&self) -> impl Iterator<Item = (OutputId, InputId, &[Pos2])> + '_ {
+        self.wires.iter().flat_map(|(input, wires)| {
+            wires
+                .iter()
+                .map(move |(output, knots)| (*output, input, knots.as_slice()))
+        })
+    }
+
+    /// Discards the knots of the wires whose connection is no longer part of
+    /// `graph`.
+    pub fn retain_connections<NodeData, DataType, ValueType>(
+        &mut self,
+        graph: &Graph<NodeData, DataType, ValueType>,
+    ) {
+        self.wires.retain(|input, wires| {
+            let connections = graph.input_connections(input);
+            wires.retain(|(output, _)| connections.contains(output));
+            !wires.is_empty()
+        });
+    }
+}
+
+/// The control points of the cubic bezier curves drawing a wire from
+/// `src_pos`, through `knots`, to `dst_pos`. The wire leaves every point
+/// horizontally, so the curves join smoothly at the knots.
+pub(crate) fn wire_curves(src_pos: Pos2, knots: &[Pos2], dst_pos: Pos2) -> Vec<[Pos2; 4]> {
+    let points: Vec<Pos2> = std::iter::once(src_pos)
+        .chain(knots.iter().copied())
+        .chain(std::iter::once(dst_pos))
+        .collect();
+    points
+        .windows(2)
+        .map(|segment| {
+            let (start, end) = (segment[0], segment[1]);
+            let control_scale = ((end.x - start.x) / 2.0).max(30.0);
+            [
+                start,
+                start + Vec2::X * control_scale,
+                end - Vec2::X * control_scale,
+                end,
+            ]
+        })
+        .collect()
+}
+
+fn distance_to_segment(pos: Pos2, start: Pos2, end: Pos2) -> f32 {
+    let segment = end - start;
+    let t = if segment.length_sq() > 0.0 {
+        ((pos - start).dot(segment) / segment.length_sq()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    pos.distance(start + segment * t)
+}
+
+/// Returns the curve of a wire closest to `pos`, as returned by
+/// [`wire_curves`], and its distance to `pos`. The index of the curve is also
+/// the index of a knot inserted at `pos`.
+fn closest_curve(curves: &[[Pos2; 4]], pos: Pos2) -> Option<(usize, f32)> {
+    curves
+        .iter()
+        .enumerate()
+        .map(|(index, points)| {
+            let bezier = CubicBezierShape::from_points_stroke(
+                *points,
+                false,
+                Color32::TRANSPARENT,
+                Stroke::NONE,
+            );
+            let distance = bezier
+                .flatten(Some(1.0))
+                .windows(2)
+                .map(|line| distance_to_segment(pos, line[0], line[1]))
+                .fold(f32::INFINITY, f32::min);
+            (index, distance)
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// A wire drawn by the editor, with the screen positions of its ports.
+pub(crate) struct DrawnWire {
+    pub output: OutputId,
+    pub input: InputId,
+    pub src_pos: Pos2,
+    pub dst_pos: Pos2,
+    pub color: Color32,
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+{
+    /// The screen positions of the knots of a wire of the open graph.
+    pub(crate) fn knot_positions(&self, output: OutputId, input: InputId, pan: Vec2) -> Vec<Pos2> {
+        self.knots
+            .get(output, input)
+            .iter()
+            .map(|knot| *knot + pan)
+            .collect()
+    }
+
+    /// Draws the knots of the `wires` of the open graph, which must be swapped
+    /// in, once the wires are drawn. Dragging a knot moves it, and double
+    /// clicking it removes it. When the background of the editor was double
+    /// clicked next to a wire, a knot is inserted there.
+    pub(crate) fn show_knots<UserResponse>(
+        &self,
+        ui: &mut egui::Ui,
+        pan: Vec2,
+        wires: &[DrawnWire],
+        double_click_pos: Option<Pos2>,
+    ) -> Vec<NodeResponse<UserResponse, NodeData>>
+    where
+        UserResponse: UserResponseTrait,
+        NodeData: NodeDataTrait,
+    {
+        let mut responses = Vec::new();
+        for wire in wires {
+            let (output, input) = (wire.output, wire.input);
+            for (index, pos) in self
+                .knot_positions(output, input, pan)
+                .into_iter()
+                .enumerate()
+            {
+                let knot_resp = ui.interact(
+                    Rect::from_center_size(pos, Vec2::splat(KNOT_RADIUS * 2.0 + 2.0)),
+                    Id::new((output, input, index, "knot")),
+                    Sense::click_and_drag(),
+                );
+                let stroke = if knot_resp.hovered() || knot_resp.dragged() {
+                    Stroke::new(2.0, Color32::WHITE)
+                } else {
+                    Stroke::NONE
+                };
+                ui.painter().circle(pos, KNOT_RADIUS, wire.color, stroke);
+
+                if knot_resp.double_clicked() {
+                    responses.push(NodeResponse::RemoveKnot {
+                        output,
+                        input,
+                        index,
+                    });
+                }
+                let drag_delta = knot_resp.drag_delta();
+                if drag_delta.length_sq() > 0.0 {
+                    responses.push(NodeResponse::MoveKnot {
+                        output,
+                        input,
+                        index,
+                        drag_delta,
+                    });
+                }
+            }
+        }
+
+        if let Some(pos) = double_click_pos {
+            let closest = wires
+                .iter()
+                .filter_map(|wire| {
+                    let knots = self.knot_positions(wire.output, wire.input, pan);
+                    let curves = wire_curves(wire.src_pos, &knots, wire.dst_pos);
+                    closest_curve(&curves, pos).map(|(index, distance)| (wire, index, distance))
+                })
+                .min_by(|a, b| a.2.total_cmp(&b.2));
+            if let Some((wire, index, distance)) = closest {
+                if distance < DISTANCE_TO_WIRE {
+                    responses.push(NodeResponse::AddKnot {
+                        output: wire.output,
+                        input: wire.input,
+                        index,
+                        position: pos - pan,
+                    });
+                }
+            }
+        }
+        responses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_knots() {
+        let mut graph = Graph::<(), (), ()>::new();
+        let a = graph.add_node("a".into(), (), |graph, node_id| {
+            graph.add_output_param(node_id, "out".into(), ());
+        });
+        let b = graph.add_node("b".into(), (), |graph, node_id| {
+            graph.add_input_param(
+                node_id,
+                "in".into(),
+                (),
+                (),
+                InputParamKind::ConnectionOnly,
+                true,
+            );
+        });
+        let output = graph[a].get_output("out").unwrap();
+        let input = graph[b].get_input("in").unwrap();
+        graph.add_connection(output, input);
+
+        let mut knots = WireKnots::default();
+        knots.insert(output, input, 0, egui::pos2(10.0, 0.0));
+        knots.insert(output, input, 5, egui::pos2(30.0, 0.0));
+        knots.insert(output, input, 1, egui::pos2(20.0, 0.0));
+        assert_eq!(
+            knots.get(output, input),
+            &[
+                egui::pos2(10.0, 0.0),
+                egui::pos2(20.0, 0.0),
+                egui::pos2(30.0, 0.0)
+            ]
+        );
+        assert_eq!(knots.remove(output, input, 1), Some(egui::pos2(20.0, 0.0)));
+        assert_eq!(knots.iter().count(), 1);
+
+        // A wire through two knots is drawn as three curves, and double
+        // clicking on the middle one inserts a knot between the two knots.
+        let curves = wire_curves(Pos2::ZERO, knots.get(output, input), egui::pos2(40.0, 0.0));
+        assert_eq!(curves.len(), 3);
+        assert_eq!(curves[1][0], egui::pos2(10.0, 0.0));
+        assert_eq!(curves[1][3], egui::pos2(30.0, 0.0));
+        let (index, distance) = closest_curve(&curves, egui::pos2(20.0, 1.0)).unwrap();
+        assert_eq!(index, 1);
+        assert!(distance < DISTANCE_TO_WIRE);
+
+        // Knots are invisible to the graph, and go away with their connection
+        assert_eq!(graph.input_connections(input), &[output]);
+        graph.disconnect(output, input);
+        knots.retain_connections(&graph);
+        assert!(knots.is_empty());
+    }
+}
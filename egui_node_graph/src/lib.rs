@@ -19,6 +19,37 @@ pub mod index_impls;
 /// Implementing the main methods for the `Graph`
 pub mod graph_impls;
 
+/// Wildcard parameters, whose type is inferred from their connections
+pub mod wildcards;
+
+/// Variadic inputs, which grow and shrink as their slots get connected
+pub mod variadic;
+pub use variadic::*;
+
+/// Bypassed nodes, which forward their inputs instead of being evaluated
+pub mod bypass;
+
+/// Graph algorithms: Topological ordering and cycle detection
+pub mod algorithms;
+pub use algorithms::*;
+
+/// Traversal queries: Upstream and downstream iteration, reachability,
+/// strongly connected components and paths between nodes
+pub mod traversal;
+pub use traversal::*;
+
+/// Observers notified of the structural changes of a graph
+pub mod observer;
+pub use observer::*;
+
+/// Evaluation of the graph: Resolving node inputs, evaluating dependencies in
+/// order and caching the computed outputs
+pub mod evaluation;
+pub use evaluation::*;
+
+/// Consistency checks for graphs and editor states
+pub mod validation;
+
 /// Custom error types, crate-wide
 pub mod error;
 pub use error::*;
@@ -28,6 +59,42 @@ pub use error::*;
 pub mod ui_state;
 pub use ui_state::*;
 
+/// Undo / redo history for the graph editor
+pub mod history;
+pub use history::*;
+
+/// Group nodes: Collapsing nodes into nested graphs, and editing them
+pub mod groups;
+
+/// Comment frames, drawn behind the nodes to document regions of the graph
+pub mod frames;
+pub use frames::*;
+
+/// Reroute points, or knots, steering the wires between the nodes
+pub mod knots;
+pub use knots::*;
+
+/// Merging graphs into other graphs, with the id remapping of their nodes
+pub mod merge;
+pub use merge::*;
+
+/// Copying nodes, and pasting them into the same or other graphs
+pub mod clipboard;
+pub use clipboard::*;
+
+/// A stable graph file format, independent of the in-memory representation
+pub mod interchange;
+pub use interchange::*;
+
+/// Exporting graphs as Graphviz DOT and Mermaid diagrams
+pub mod export;
+
+/// Versioned save files, which are migrated when loaded by newer releases
+#[cfg(feature = "persistence")]
+pub mod versioning;
+#[cfg(feature = "persistence")]
+pub use versioning::*;
+
 /// The node finder is a tiny widget allowing to create new node types
 pub mod node_finder;
 pub use node_finder::*;
@@ -44,3 +111,6 @@ pub use traits::*;
 mod utils;
 
 mod color_hex_utils;
+
+#[cfg(test)]
+mod test_utils;
@@ -0,0 +1,280 @@
+use super::*;
+
+/// The new ids of the nodes and parameters of a graph that was merged into
+/// another one, indexed by their old ids. See [`Graph::merge`].
+#[derive(Debug, Clone, Default)]
+pub struct IdRemap {
+    pub nodes: SecondaryMap<NodeId, NodeId>,
+    pub inputs: SecondaryMap<InputId, InputId>,
+    pub outputs: SecondaryMap<OutputId, OutputId>,
+}
+
+impl IdRemap {
+    /// The new id of a node, if it was part of the merged graph.
+    pub fn node(&self, node: NodeId) -> Option<NodeId> {
+        self.nodes.get(node).copied()
+    }
+
+    /// The new id of an input, if it was part of the merged graph.
+    pub fn input(&self, input: InputId) -> Option<InputId> {
+        self.inputs.get(input).copied()
+    }
+
+    /// The new id of an output, if it was part of the merged graph.
+    pub fn output(&self, output: OutputId) -> Option<OutputId> {
+        self.outputs.get(output).copied()
+    }
+
+    /// The new id of an input or output, if it was part of the merged graph.
+    pub fn param(&self, param: AnyParameterId) -> Option<AnyParameterId> {
+        match param {
+            AnyParameterId::Input(input) => self.input(input).map(AnyParameterId::Input),
+            AnyParameterId::Output(output) => self.output(output).map(AnyParameterId::Output),
+        }
+    }
+}
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
+    /// Moves all the nodes of `other` into this graph, keeping their
+    /// connections and nested groups. The nodes and parameters get new ids in
+    /// this graph, which are returned so user code can update any ids it
+    /// stores. All the merged nodes are marked as dirty.
+    ///
+    /// Parameters and connections of `other` referring to missing nodes or
+    /// parameters are dropped.
+    pub fn merge(&mut self, other: Self) -> IdRemap {
+        let Graph {
+            mut nodes,
+            mut inputs,
+            mut outputs,
+            connections,
+            mut groups,
+            ..
+        } = other;
+
+        let mut remap = IdRemap::default();
+        let node_keys: Vec<NodeId> = nodes.keys().collect();
+        for node_id in node_keys {
+            let node = nodes.remove(node_id).expect("Node should exist");
+            let new_id = self.add_node(node.label, node.user_data, |graph, new_id| {
+                for (name, input) in node.inputs {
+                    if let Some(param) = inputs.remove(input) {
+                        let new_input = graph.add_input_param_like(new_id, name, param);
+                        remap.inputs.insert(input, new_input);
+                    }
+                }
+                for (name, output) in node.outputs {
+                    if let Some(param) = outputs.remove(output) {
+                        let new_output = graph.add_output_param_like(new_id, name, param);
+                        remap.outputs.insert(output, new_output);
+                    }
+                }
+            });
+            self.set_bypassed(new_id, node.bypassed);
+            remap.nodes.insert(node_id, new_id);
+
+            if let Some(mut group) = groups.remove(node_id) {
+                group.remap_ports(&remap.inputs, &remap.outputs);
+                self.groups.insert(new_id, group);
+            }
+        }
+
+        for (input, connected) in connections {
+            for output in connected {
+                if let (Some(output), Some(input)) = (remap.output(output), remap.input(input)) {
+                    self.add_connection(output, input);
+                }
+            }
+        }
+        remap
+    }
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+{
+    /// Merges the root graph of `other` into the open graph, see
+    /// [`Graph::merge`]. The merged nodes keep their draw order and their
+    /// positions, moved by `offset`, and are drawn on top of the existing
+    /// nodes. The knots of the merged wires and the frames of `other` are
+    /// moved by `offset` too, and the frames are drawn on top of the existing
+    /// frames. The rest of the state of `other` is discarded.
+    pub fn merge(
+        &mut self,
+        other: GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>,
+        offset: egui::Vec2,
+    ) -> IdRemap {
+        let frames = other
+            .frame_order
+            .iter()
+            .filter_map(|frame| other.frames.get(*frame).cloned())
+            .collect();
+        self.with_open_group(|state| {
+            state.merge_in_open_graph(
+                other.graph,
+                &other.node_order,
+                &other.node_positions,
+                &other.knots,
+                frames,
+                offset,
+            )
+        })
+    }
+
+    /// Merges `graph` into the open graph of a state that has the open group
+    /// swapped in. The merged nodes are drawn in `node_order`, followed by
+    /// the nodes missing from it, and placed at their position in
+    /// `node_positions`, or the origin, moved by `offset`. The knots of the
+    /// merged wires and the `frames`, in draw order, are moved by `offset`
+    /// too.
+    pub(crate) fn merge_in_open_graph(
+        &mut self,
+        graph: Graph<NodeData, DataType, ValueType>,
+        node_order: &[NodeId],
+        node_positions: &SecondaryMap<NodeId, egui::Pos2>,
+        knots: &WireKnots,
+        frames: Vec<CommentFrame>,
+        offset: egui::Vec2,
+    ) -> IdRemap {
+        let remap = self.graph.merge(graph);
+        self.place_merged_nodes(&remap, node_order, node_positions, knots, frames, offset);
+        remap
+    }
+
+    /// Places the nodes, knots and frames of a graph that was merged into the
+    /// open graph, see [`GraphEditorState::merge_in_open_graph`].
+    pub(crate) fn place_merged_nodes(
+        &mut self,
+        remap: &IdRemap,
+        node_order: &[NodeId],
+        node_positions: &SecondaryMap<NodeId, egui::Pos2>,
+        knots: &WireKnots,
+        frames: Vec<CommentFrame>,
+        offset: egui::Vec2,
+    ) {
+        let merged: Vec<NodeId> = node_order
+            .iter()
+            .copied()
+            .chain(remap.nodes.keys().filter(|node| !node_order.contains(node)))
+            .filter_map(|node| remap.node(node))
+            .collect();
+        for (node, new_id) in &remap.nodes {
+            let pos = node_positions
+                .get(node)
+                .copied()
+                .unwrap_or(egui::Pos2::ZERO);
+            self.node_positions.insert(*new_id, pos + offset);
+        }
+        self.node_order.extend(merged);
+
+        for (output, input, wire_knots) in knots.iter() {
+            if let (Some(output), Some(input)) = (remap.output(output), remap.input(input)) {
+                let wire_knots = wire_knots.iter().map(|knot| *knot + offset).collect();
+                self.knots.set(output, input, wire_knots);
+            }
+        }
+        for mut frame in frames {
+            frame.position += offset;
+            let frame_id = self.frames.insert(frame);
+            self.frame_order.push(frame_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    pub fn test_merge() {
+        let mut graph = TestGraph::new();
+        let existing = add_node(&mut graph, &["in"]);
+
+        let mut other = TestGraph::new();
+        let a = add_node(&mut other, &["in"]);
+        let b = add_node(&mut other, &["in"]);
+        let a_out = other[a].get_output("out").unwrap();
+        let b_in = other[b].get_input("in").unwrap();
+        other.add_connection(a_out, b_in);
+
+        let remap = graph.merge(other);
+        assert_eq!(graph.nodes.len(), 3);
+        assert_eq!(remap.nodes.len(), 2);
+
+        let (new_a, new_b) = (remap.node(a).unwrap(), remap.node(b).unwrap());
+        assert!(new_a != existing && new_b != existing && new_a != new_b);
+        assert_eq!(graph.successors(new_a), vec![new_b]);
+        assert_eq!(
+            graph.input_connections(remap.input(b_in).unwrap()),
+            &[remap.output(a_out).unwrap()]
+        );
+        assert_eq!(graph[remap.output(a_out).unwrap()].node, new_a);
+        assert!(graph.successors(existing).is_empty());
+    }
+
+    #[test]
+    pub fn test_merge_missing_params() {
+        let mut graph = TestGraph::new();
+        let mut other = TestGraph::new();
+        let a = add_node(&mut other, &["in"]);
+        let b = add_node(&mut other, &["in"]);
+        let a_out = other[a].get_output("out").unwrap();
+        let b_in = other[b].get_input("in").unwrap();
+        other.add_connection(a_out, b_in);
+
+        // The output is listed by its node and connected, but missing
+        other.outputs.remove(a_out);
+        let remap = graph.merge(other);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(remap.output(a_out), None);
+        assert!(graph[remap.node(a).unwrap()].outputs.is_empty());
+        assert!(graph
+            .input_connections(remap.input(b_in).unwrap())
+            .is_empty());
+    }
+
+    #[test]
+    pub fn test_merge_editor_state() {
+        type TestState = GraphEditorState<(), Type, (), (), ()>;
+        let mut other = TestState::default();
+        let a = add_node(&mut other.graph, &["in"]);
+        let b = add_node(&mut other.graph, &["in"]);
+        let a_out = other.graph[a].get_output("out").unwrap();
+        let b_in = other.graph[b].get_input("in").unwrap();
+        other.graph.add_connection(a_out, b_in);
+        other.node_order = vec![a, b];
+        other.node_positions.insert(a, egui::pos2(10.0, 10.0));
+        other.node_positions.insert(b, egui::pos2(100.0, 10.0));
+        other.knots.insert(a_out, b_in, 0, egui::pos2(50.0, 50.0));
+        other.add_frame(CommentFrame::new(
+            "frame",
+            egui::pos2(0.0, 0.0),
+            egui::vec2(200.0, 100.0),
+        ));
+
+        // Copying the nodes takes the knots of their wire and their frame
+        let contents = other.copy_nodes(&[a, b]);
+        assert_eq!(contents.frames.len(), 1);
+        assert_eq!(contents.frames[0].position, egui::pos2(-10.0, -10.0));
+
+        let offset = egui::vec2(1000.0, 0.0);
+        let mut state = TestState::default();
+        let remap = state.merge(other, offset);
+        let (new_out, new_in) = (remap.output(a_out).unwrap(), remap.input(b_in).unwrap());
+        assert_eq!(
+            state.knots.get(new_out, new_in),
+            &[egui::pos2(1050.0, 50.0)]
+        );
+        assert_eq!(state.frame_order.len(), 1);
+        let frame = &state.frames[state.frame_order[0]];
+        assert_eq!(frame.position, egui::pos2(1000.0, 0.0));
+
+        let pasted = state.paste(contents, egui::pos2(0.0, 500.0));
+        assert_eq!(pasted.len(), 2);
+        assert_eq!(state.knots.iter().count(), 2);
+        assert_eq!(state.frame_order.len(), 2);
+        let frame = &state.frames[state.frame_order[1]];
+        assert_eq!(frame.position, egui::pos2(-10.0, 490.0));
+    }
+}
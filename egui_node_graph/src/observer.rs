@@ -0,0 +1,304 @@
+use super::*;
+use std::sync::{Arc, Mutex};
+
+/// A structural change of a [`Graph`], reported to its observers. See
+/// [`GraphObserverTrait`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphEvent {
+    /// A node was added. Its parameters are reported by the events that
+    /// follow.
+    NodeAdded(NodeId),
+    /// A node was removed, after its connections and parameters.
+    NodeRemoved(NodeId),
+    InputAdded {
+        node: NodeId,
+        input: InputId,
+    },
+    InputRemoved {
+        node: NodeId,
+        input: InputId,
+    },
+    OutputAdded {
+        node: NodeId,
+        output: OutputId,
+    },
+    OutputRemoved {
+        node: NodeId,
+        output: OutputId,
+    },
+    /// A parameter was renamed, moved inside its node, or changed type.
+    ParamChanged(AnyParameterId),
+    /// A node was bypassed, or enabled again. See [`Node::bypassed`].
+    BypassChanged(NodeId),
+    Connected {
+        output: OutputId,
+        input: InputId,
+    },
+    Disconnected {
+        output: OutputId,
+        input: InputId,
+    },
+    /// The whole contents of the graph were replaced, for instance when the
+    /// editor undoes an edit. Observers should rebuild their state from the
+    /// graph.
+    Replaced,
+}
+
+/// Observers are notified of every structural change of the [`Graph`] they
+/// are added to with [`Graph::add_observer`]: Added and removed nodes and
+/// parameters, changed parameters, bypassed nodes, and connections. This allows user code to
+/// keep side data, like a search index, in sync with the graph, no matter
+/// whether the graph is modified by the editor or by the methods of the
+/// graph.
+///
+/// Changes made by modifying the public fields of the graph directly are not
+/// reported. Each nested graph of a group node has its own observers, but the
+/// changes made by the editor to the nested graphs of open groups are
+/// forwarded to the observers of the root graph too, see
+/// [`GraphObserverTrait::on_group_event`].
+pub trait GraphObserverTrait<NodeData, DataType, ValueType>: Send {
+    /// Called right after each change, with the modified graph. Events are
+    /// reported one at a time, so while a node is being added or removed,
+    /// the graph may still be missing some of its parameters or connections.
+    fn on_graph_event(&mut self, graph: &Graph<NodeData, DataType, ValueType>, event: &GraphEvent);
+
+    /// Called right after each change made by the editor to the nested graph
+    /// of an open group, with the modified nested graph. The `group_path`
+    /// lists the group nodes containing the nested graph, from the outermost
+    /// one, see [`GraphEditorState::open_groups`]. The ids in `event` refer to
+    /// the nested graph.
+    ///
+    /// Default implementation ignores the changes of nested graphs.
+    fn on_group_event(
+        &mut self,
+        _group_path: &[NodeId],
+        _graph: &Graph<NodeData, DataType, ValueType>,
+        _event: &GraphEvent,
+    ) {
+    }
+}
+
+/// An observer shared between user code and the [`Graph`] it observes. Don't
+/// keep it locked while modifying the graph, since the graph locks it to
+/// report changes.
+pub type SharedObserver<NodeData, DataType, ValueType> =
+    Arc<Mutex<dyn GraphObserverTrait<NodeData, DataType, ValueType>>>;
+
+/// The observers of a [`Graph`]. Observers are not cloned along with the
+/// graph, so they only hear about the graph they were added to, and not about
+/// its copies, like the snapshots of the undo history.
+pub struct GraphObservers<NodeData, DataType, ValueType> {
+    observers: SlotMap<ObserverId, SharedObserver<NodeData, DataType, ValueType>>,
+    /// The observers of the root graph, while this graph is the nested graph
+    /// of an open group swapped into the editor, and the path of that group.
+    root_observers: SlotMap<ObserverId, SharedObserver<NodeData, DataType, ValueType>>,
+    group_path: Vec<NodeId>,
+}
+
+impl<NodeData, DataType, ValueType> Default for GraphObservers<NodeData, DataType, ValueType> {
+    fn default() -> Self {
+        Self {
+            observers: SlotMap::default(),
+            root_observers: SlotMap::default(),
+            group_path: Vec::new(),
+        }
+    }
+}
+
+impl<NodeData, DataType, ValueType> GraphObservers<NodeData, DataType, ValueType> {
+    /// Borrows the observers of the `root` graph, which hear about the changes
+    /// of this graph until [`GraphObservers::stop_forwarding`] is called.
+    pub(crate) fn forward_to(&mut self, root: &mut Self, group_path: Vec<NodeId>) {
+        self.root_observers = std::mem::take(&mut root.observers);
+        self.group_path = group_path;
+    }
+
+    /// Gives the observers borrowed by [`GraphObservers::forward_to`] back.
+    pub(crate) fn stop_forwarding(&mut self, root: &mut Self) {
+        root.observers = std::mem::take(&mut self.root_observers);
+        self.group_path.clear();
+    }
+}
+
+impl<NodeData, DataType, ValueType> Clone for GraphObservers<NodeData, DataType, ValueType> {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl<NodeData, DataType, ValueType> std::fmt::Debug
+    for GraphObservers<NodeData, DataType, ValueType>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GraphObservers")
+            .field("len", &self.observers.len())
+            .finish()
+    }
+}
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
+    /// Adds an observer, which is notified of every structural change of this
+    /// graph from now on. See [`GraphObserverTrait`].
+    pub fn add_observer(
+        &mut self,
+        observer: SharedObserver<NodeData, DataType, ValueType>,
+    ) -> ObserverId {
+        self.observers.observers.insert(observer)
+    }
+
+    pub fn remove_observer(
+        &mut self,
+        observer_id: ObserverId,
+    ) -> Option<SharedObserver<NodeData, DataType, ValueType>> {
+        self.observers.observers.remove(observer_id)
+    }
+
+    /// Reports `event` to the observers of the graph.
+    pub(crate) fn notify(&self, event: GraphEvent) {
+        for observer in self.observers.observers.values() {
+            // An observer that panicked while locked still gets the events
+            let mut observer = observer.lock().unwrap_or_else(|err| err.into_inner());
+            observer.on_graph_event(self, &event);
+        }
+        for observer in self.observers.root_observers.values() {
+            let mut observer = observer.lock().unwrap_or_else(|err| err.into_inner());
+            observer.on_group_event(&self.observers.group_path, self, &event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct EventLog(Vec<GraphEvent>);
+
+    impl GraphObserverTrait<(), (), ()> for EventLog {
+        fn on_graph_event(&mut self, _graph: &Graph<(), (), ()>, event: &GraphEvent) {
+            self.0.push(*event);
+        }
+    }
+
+    /// Logs the events of the nested graphs, with their group path.
+    #[derive(Default)]
+    struct GroupEventLog(Vec<(Vec<NodeId>, GraphEvent)>);
+
+    impl GraphObserverTrait<(), (), ()> for GroupEventLog {
+        fn on_graph_event(&mut self, _graph: &Graph<(), (), ()>, event: &GraphEvent) {
+            self.0.push((vec![], *event));
+        }
+
+        fn on_group_event(
+            &mut self,
+            group_path: &[NodeId],
+            _graph: &Graph<(), (), ()>,
+            event: &GraphEvent,
+        ) {
+            self.0.push((group_path.to_vec(), *event));
+        }
+    }
+
+    #[test]
+    pub fn test_observer() {
+        let mut graph = Graph::<(), (), ()>::new();
+        let log = Arc::new(Mutex::new(EventLog::default()));
+        let observer_id = graph.add_observer(log.clone());
+        let take_events = || std::mem::take(&mut log.lock().unwrap().0);
+
+        let a = graph.add_node("a".into(), (), |graph, node_id| {
+            graph.add_output_param(node_id, "out".into(), ());
+        });
+        let output = graph[a].get_output("out").unwrap();
+        let b = graph.add_node("b".into(), (), |graph, node_id| {
+            graph.add_input_param(
+                node_id,
+                "in".into(),
+                (),
+                (),
+                InputParamKind::ConnectionOnly,
+                true,
+            );
+        });
+        let input = graph[b].get_input("in").unwrap();
+        graph.add_connection(output, input);
+        assert_eq!(
+            take_events(),
+            vec![
+                GraphEvent::NodeAdded(a),
+                GraphEvent::OutputAdded { node: a, output },
+                GraphEvent::NodeAdded(b),
+                GraphEvent::InputAdded { node: b, input },
+                GraphEvent::Connected { output, input },
+            ]
+        );
+
+        // Connecting the same parameters again changes nothing
+        graph.add_connection(output, input);
+        assert_eq!(take_events(), vec![]);
+
+        graph.set_bypassed(b, true);
+        graph.set_bypassed(b, true);
+        assert_eq!(take_events(), vec![GraphEvent::BypassChanged(b)]);
+
+        graph.remove_node(a);
+        assert_eq!(
+            take_events(),
+            vec![
+                GraphEvent::Disconnected { output, input },
+                GraphEvent::OutputRemoved { node: a, output },
+                GraphEvent::NodeRemoved(a),
+            ]
+        );
+
+        // Copies of the graph, and the graph once the observer is removed,
+        // don't report to the observer.
+        graph.clone().remove_node(b);
+        graph.remove_observer(observer_id);
+        graph.remove_node(b);
+        assert_eq!(take_events(), vec![]);
+    }
+
+    #[test]
+    pub fn test_group_events() {
+        type TestState = GraphEditorState<(), (), (), (), ()>;
+        let add_node = |graph: &mut Graph<(), (), ()>| {
+            graph.add_node("node".into(), (), |graph, node_id| {
+                graph.add_output_param(node_id, "out".into(), ());
+            })
+        };
+        let mut state = TestState::default();
+        let a = add_node(&mut state.graph);
+        let group_id = state.group_nodes(&[a], "group".into(), ());
+        let log = Arc::new(Mutex::new(GroupEventLog::default()));
+        state.graph.add_observer(log.clone());
+        let take_events = || std::mem::take(&mut log.lock().unwrap().0);
+
+        // Edits inside the open group are reported with the group path
+        state.open_group(group_id);
+        let mut other = TestState::default();
+        let b = add_node(&mut other.graph);
+        let remap = state.merge(other, egui::Vec2::ZERO);
+        let new_b = remap.node(b).unwrap();
+        let new_out = state.open_graph()[new_b].get_output("out").unwrap();
+        assert_eq!(
+            take_events(),
+            vec![
+                (vec![group_id], GraphEvent::NodeAdded(new_b)),
+                (
+                    vec![group_id],
+                    GraphEvent::OutputAdded {
+                        node: new_b,
+                        output: new_out
+                    }
+                ),
+            ]
+        );
+
+        // Once the group is closed, edits of the root graph are reported as
+        // usual
+        state.close_group();
+        let c = add_node(&mut state.graph);
+        assert_eq!(take_events()[0], (vec![], GraphEvent::NodeAdded(c)));
+    }
+}
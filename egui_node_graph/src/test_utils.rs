@@ -0,0 +1,63 @@
+use super::*;
+
+/// The data type of the graphs built by the unit tests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum Type {
+    Any,
+    Float,
+    Vec2,
+    Text,
+}
+
+impl DataTypeTrait<()> for Type {
+    fn data_type_color(&self, _user_state: &mut ()) -> egui::Color32 {
+        match self {
+            Type::Any => egui::Color32::WHITE,
+            Type::Float => egui::Color32::from_rgb(0xff, 0x80, 0x00),
+            Type::Vec2 => egui::Color32::from_rgb(0x00, 0x80, 0xff),
+            Type::Text => egui::Color32::from_rgb(0x80, 0xff, 0x00),
+        }
+    }
+
+    fn name(&self) -> std::borrow::Cow<'_, str> {
+        std::borrow::Cow::Borrowed(match self {
+            Type::Any => "any",
+            Type::Float => "float",
+            Type::Vec2 => "vec2",
+            Type::Text => "text",
+        })
+    }
+}
+
+pub type TestGraph = Graph<(), Type, ()>;
+
+/// Adds a node with the given inputs and an output named `out`, all of them
+/// of type [`Type::Float`].
+pub fn add_node(graph: &mut TestGraph, inputs: &[&str]) -> NodeId {
+    add_typed_node(graph, Type::Float, inputs)
+}
+
+/// Like [`add_node`], with parameters of type `typ`.
+pub fn add_typed_node(graph: &mut TestGraph, typ: Type, inputs: &[&str]) -> NodeId {
+    graph.add_node("node".into(), (), |graph, node_id| {
+        for name in inputs {
+            graph.add_input_param(
+                node_id,
+                name.to_string(),
+                typ,
+                (),
+                InputParamKind::ConnectionOnly,
+                true,
+            );
+        }
+        graph.add_output_param(node_id, "out".into(), typ);
+    })
+}
+
+/// Connects the `out` output of `from` to the input of `to` named `input`.
+pub fn connect(graph: &mut TestGraph, from: NodeId, to: NodeId, input: &str) {
+    let output = graph[from].get_output("out").unwrap();
+    let input = graph[to].get_input(input).unwrap();
+    graph.add_connection(output, input);
+}
@@ -18,7 +18,8 @@ pub trait WidgetValueTrait: Default {
     /// input only. To display UI for connected inputs use [`WidgetValueTrait::value_widget_connected`].
     /// The return value is a vector of custom response objects which can be used
     /// to implement handling of side effects. If unsure, the response Vec can
-    /// be empty.
+    /// be empty. Modifications of the value should be reported with a response
+    /// for which [`UserResponseTrait::is_value_changed`] returns true.
     fn value_widget(
         &mut self,
         param_name: &str,
@@ -85,6 +86,25 @@ pub trait DataTypeTrait<UserState>: PartialEq + Eq {
     /// }
     /// ```
     fn name(&self) -> std::borrow::Cow<str>;
+
+    /// Whether an output of this datatype can be connected to an input of the
+    /// `target` datatype. The editor only offers the ports that pass this
+    /// check as targets of a connection. Defaults to equality, override it to
+    /// support subtypes or implicit conversions.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// fn can_connect_to(&self, target: &Self, _user_state: &mut MyUserState) -> bool {
+    ///     match (self, target) {
+    ///         (MyDataType::Int, MyDataType::Float) => true,
+    ///         (_, MyDataType::Any) => true,
+    ///         _ => self == target,
+    ///     }
+    /// }
+    /// ```
+    fn can_connect_to(&self, target: &Self, _user_state: &mut UserState) -> bool {
+        self == target
+    }
 }
 
 /// This trait must be implemented for the `NodeData` generic parameter of the
@@ -176,6 +196,22 @@ where
     ) {
     }
 
+    /// Extra contents for the tooltip shown when hovering a port, below the
+    /// parameter name, the name of its data type and its connections. Useful
+    /// to describe the data type in more detail, or to show the current value
+    /// of the parameter.
+    ///
+    /// Default implementation does nothing.
+    fn port_tooltip_ui(
+        &self,
+        _ui: &mut egui::Ui,
+        _node_id: NodeId,
+        _param_id: AnyParameterId,
+        _graph: &Graph<Self, Self::DataType, Self::ValueType>,
+        _user_state: &mut Self::UserState,
+    ) {
+    }
+
     fn can_delete(
         &self,
         _node_id: NodeId,
@@ -184,6 +220,52 @@ where
     ) -> bool {
         true
     }
+
+    /// Whether the node shows a toggle to bypass it in its title bar. See
+    /// [`Node::bypassed`].
+    ///
+    /// Default implementation returns `false`, so bypassing nodes from the UI
+    /// is disabled unless this method is implemented.
+    fn can_bypass(
+        &self,
+        _node_id: NodeId,
+        _graph: &Graph<Self, Self::DataType, Self::ValueType>,
+        _user_state: &mut Self::UserState,
+    ) -> bool {
+        false
+    }
+
+    /// Called while the user drags a connection from `output` towards the
+    /// `input` of this node, when the connection would introduce a cycle in
+    /// the graph and the editor's [`CyclePolicy`] is set to
+    /// [`CyclePolicy::AskUser`]. Returns whether the connection is allowed.
+    ///
+    /// Default implementation rejects the connection.
+    fn allow_cycle(
+        &self,
+        _node_id: NodeId,
+        _output: OutputId,
+        _input: InputId,
+        _graph: &Graph<Self, Self::DataType, Self::ValueType>,
+        _user_state: &mut Self::UserState,
+    ) -> bool {
+        false
+    }
+
+    /// Called when the user presses Ctrl+G to collapse the selected `nodes`
+    /// into a group node, see [`GraphEditorState::handle_group_shortcut`].
+    /// Returns the label and the user data of the group node, or `None` to
+    /// prevent the grouping.
+    ///
+    /// Default implementation returns `None`, so grouping nodes from the UI
+    /// is disabled unless this method is implemented.
+    fn group_node_data(
+        _nodes: &[NodeId],
+        _graph: &Graph<Self, Self::DataType, Self::ValueType>,
+        _user_state: &mut Self::UserState,
+    ) -> Option<(String, Self)> {
+        None
+    }
 }
 
 /// This trait can be implemented by any user type. The trait tells the library
@@ -277,8 +359,46 @@ pub trait NodeTemplateTrait: Clone {
         user_state: &mut Self::UserState,
         node_id: NodeId,
     );
+
+    /// Returns the variadic inputs of this node kind, added to new nodes after
+    /// the parameters created by [`NodeTemplateTrait::build_node`]. The
+    /// editor grows and shrinks them as their slots get connected, see
+    /// [`Graph::add_variadic_input_param`].
+    ///
+    /// Default implementation returns no variadic inputs.
+    fn variadic_inputs(
+        &self,
+        _user_state: &mut Self::UserState,
+    ) -> Vec<VariadicInput<Self::DataType, Self::ValueType>> {
+        Vec::new()
+    }
+
+    /// Returns the template of a node converting values of the `source` type
+    /// to the `target` type, if any. When the user drops a connection between
+    /// ports of incompatible types, the editor inserts such a node between
+    /// them instead of rejecting the connection. The node is connected through
+    /// its first input accepting connections from `source`, and its first
+    /// output compatible with `target`, see [`DataTypeTrait::can_connect_to`].
+    /// [`InputParamKind::ConstantOnly`] inputs are skipped.
+    fn conversion_node(
+        _source: &Self::DataType,
+        _target: &Self::DataType,
+        _user_state: &mut Self::UserState,
+    ) -> Option<Self> {
+        None
+    }
 }
 
 /// The custom user response types when drawing nodes in the graph must
 /// implement this trait.
-pub trait UserResponseTrait: Clone + std::fmt::Debug {}
+pub trait UserResponseTrait: Clone + std::fmt::Debug {
+    /// Whether this response, returned by [`WidgetValueTrait::value_widget`],
+    /// reports that the widget modified its value. The editor then emits a
+    /// [`NodeResponse::InputValueChanged`] for the input, which marks the node
+    /// as dirty and can be undone.
+    ///
+    /// Default implementation returns `false`.
+    fn is_value_changed(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,357 @@
+use super::*;
+use std::collections::{HashSet, VecDeque};
+
+/// The direction in which a [`Traversal`] follows connections.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraversalDirection {
+    /// From the inputs of a node to the outputs connected to them.
+    Upstream,
+    /// From the outputs of a node to the inputs connected to them.
+    Downstream,
+}
+
+/// The order in which a [`Traversal`] visits nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraversalOrder {
+    /// Visits the nodes closer to the start first.
+    BreadthFirst,
+    /// Follows each branch to its end before moving to the next one.
+    DepthFirst,
+}
+
+/// A path between two nodes of a [`Graph`]. See [`Graph::all_paths`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodePath {
+    /// The nodes in the path, from the first to the last one.
+    pub nodes: Vec<NodeId>,
+    /// The connections forming the path, as `(input, output)` pairs. The
+    /// connection at index `i` goes from `nodes[i]` to `nodes[i + 1]`.
+    pub connections: Vec<(InputId, OutputId)>,
+}
+
+/// An iterator over the nodes upstream or downstream from a node or a
+/// parameter, created by [`Graph::traverse`] and
+/// [`Graph::traverse_from_param`]. Each node is visited once, even if it is
+/// reached by several paths.
+pub struct Traversal<'a, NodeData, DataType, ValueType> {
+    graph: &'a Graph<NodeData, DataType, ValueType>,
+    direction: TraversalDirection,
+    order: TraversalOrder,
+    pending: VecDeque<NodeId>,
+    visited: HashSet<NodeId>,
+}
+
+impl<'a, NodeData, DataType, ValueType> Traversal<'a, NodeData, DataType, ValueType> {
+    fn new(
+        graph: &'a Graph<NodeData, DataType, ValueType>,
+        direction: TraversalDirection,
+        order: TraversalOrder,
+        start: Vec<NodeId>,
+    ) -> Self {
+        let mut traversal = Self {
+            graph,
+            direction,
+            order,
+            pending: VecDeque::new(),
+            visited: HashSet::new(),
+        };
+        traversal.push(start);
+        traversal
+    }
+
+    fn push(&mut self, nodes: Vec<NodeId>) {
+        match self.order {
+            TraversalOrder::BreadthFirst => self.pending.extend(nodes),
+            // Pushed in reverse, so that the first node is popped first
+            TraversalOrder::DepthFirst => self.pending.extend(nodes.into_iter().rev()),
+        }
+    }
+}
+
+impl<'a, NodeData, DataType, ValueType> Iterator for Traversal<'a, NodeData, DataType, ValueType> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        loop {
+            let node = match self.order {
+                TraversalOrder::BreadthFirst => self.pending.pop_front()?,
+                TraversalOrder::DepthFirst => self.pending.pop_back()?,
+            };
+            if !self.visited.insert(node) {
+                continue;
+            }
+            self.push(self.graph.neighbors(node, self.direction));
+            return Some(node);
+        }
+    }
+}
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
+    /// The nodes connected to `node_id` in the given direction, in parameter
+    /// and connection order. Nodes connected through several parameters are
+    /// repeated.
+    fn neighbors(&self, node_id: NodeId, direction: TraversalDirection) -> Vec<NodeId> {
+        match direction {
+            TraversalDirection::Upstream => self[node_id]
+                .input_ids()
+                .flat_map(|input| self.input_connections(input))
+                .map(|output| self[*output].node)
+                .collect(),
+            TraversalDirection::Downstream => self[node_id]
+                .output_ids()
+                .flat_map(|output| self.outgoing(output))
+                .map(|input| self[*input].node)
+                .collect(),
+        }
+    }
+
+    /// Iterates over the nodes upstream or downstream from `start`. The start
+    /// node itself is not returned, unless it's part of a cycle.
+    pub fn traverse(
+        &self,
+        start: NodeId,
+        direction: TraversalDirection,
+        order: TraversalOrder,
+    ) -> Traversal<'_, NodeData, DataType, ValueType> {
+        Traversal::new(self, direction, order, self.neighbors(start, direction))
+    }
+
+    /// Iterates over the nodes upstream from an input, or downstream from an
+    /// output. Only the connections of `param` are followed from its node, so
+    /// the nodes only connected to its other parameters are not returned.
+    pub fn traverse_from_param(
+        &self,
+        param: AnyParameterId,
+        order: TraversalOrder,
+    ) -> Traversal<'_, NodeData, DataType, ValueType> {
+        let (direction, pending) = match param {
+            AnyParameterId::Input(input) => (
+                TraversalDirection::Upstream,
+                self.input_connections(input)
+                    .iter()
+                    .map(|output| self[*output].node)
+                    .collect(),
+            ),
+            AnyParameterId::Output(output) => (
+                TraversalDirection::Downstream,
+                self.outgoing(output)
+                    .iter()
+                    .map(|input| self[*input].node)
+                    .collect(),
+            ),
+        };
+        Traversal::new(self, direction, order, pending)
+    }
+
+    /// Returns whether `to` can be reached from `from` by following
+    /// connections downstream. Every node is reachable from itself.
+    pub fn is_reachable(&self, from: NodeId, to: NodeId) -> bool {
+        from == to
+            || self
+                .traverse(
+                    from,
+                    TraversalDirection::Downstream,
+                    TraversalOrder::BreadthFirst,
+                )
+                .any(|node| node == to)
+    }
+
+    /// Returns the nodes without incoming connections.
+    pub fn sources(&self) -> Vec<NodeId> {
+        self.iter_nodes()
+            .filter(|node| {
+                self[*node]
+                    .input_ids()
+                    .all(|input| self.input_connections(input).is_empty())
+            })
+            .collect()
+    }
+
+    /// Returns the nodes without outgoing connections.
+    pub fn sinks(&self) -> Vec<NodeId> {
+        self.iter_nodes()
+            .filter(|node| {
+                self[*node]
+                    .output_ids()
+                    .all(|output| self.outgoing(output).is_empty())
+            })
+            .collect()
+    }
+
+    /// Returns the strongly connected components of the graph: The groups of
+    /// nodes that can all reach each other. Nodes outside of any cycle form a
+    /// component on their own.
+    ///
+    /// The components are sorted in topological order, so that every
+    /// component comes after the components connected to its inputs.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        let successors: SecondaryMap<NodeId, Vec<NodeId>> = self
+            .iter_nodes()
+            .map(|node| (node, self.neighbors(node, TraversalDirection::Downstream)))
+            .collect();
+
+        // Iterative version of Tarjan's algorithm. Each element of the call
+        // stack stores a node and the index of the next successor to explore.
+        let mut next_index = 0;
+        let mut indices = SecondaryMap::<NodeId, usize>::new();
+        let mut low_links = SecondaryMap::<NodeId, usize>::new();
+        let mut stack = Vec::new();
+        let mut on_stack = HashSet::new();
+        let mut components = Vec::new();
+
+        for root in self.iter_nodes() {
+            if indices.contains_key(root) {
+                continue;
+            }
+            let mut call_stack = vec![(root, 0)];
+            indices.insert(root, next_index);
+            low_links.insert(root, next_index);
+            next_index += 1;
+            stack.push(root);
+            on_stack.insert(root);
+
+            while let Some((node, next)) = call_stack.last_mut() {
+                let node = *node;
+                if let Some(&to) = successors[node].get(*next) {
+                    *next += 1;
+                    if !indices.contains_key(to) {
+                        indices.insert(to, next_index);
+                        low_links.insert(to, next_index);
+                        next_index += 1;
+                        stack.push(to);
+                        on_stack.insert(to);
+                        call_stack.push((to, 0));
+                    } else if on_stack.contains(&to) {
+                        low_links[node] = low_links[node].min(indices[to]);
+                    }
+                } else {
+                    call_stack.pop();
+                    if let Some((parent, _)) = call_stack.last() {
+                        low_links[*parent] = low_links[*parent].min(low_links[node]);
+                    }
+                    if low_links[node] == indices[node] {
+                        let mut component = Vec::new();
+                        while let Some(member) = stack.pop() {
+                            on_stack.remove(&member);
+                            component.push(member);
+                            if member == node {
+                                break;
+                            }
+                        }
+                        components.push(component);
+                    }
+                }
+            }
+        }
+
+        // Tarjan's algorithm finds the components in reverse topological order
+        components.reverse();
+        components
+    }
+
+    /// Returns all the paths going downstream from `from` to `to`. Paths
+    /// never visit a node twice, so cycles are not followed. Nodes connected
+    /// by several connections lead to one path per connection.
+    ///
+    /// The number of paths can grow exponentially with the size of the graph,
+    /// so this is best used on small graphs, or nodes close to each other.
+    pub fn all_paths(&self, from: NodeId, to: NodeId) -> Vec<NodePath> {
+        let edges = |node: NodeId| -> Vec<(NodeId, InputId, OutputId)> {
+            self[node]
+                .output_ids()
+                .flat_map(|output| {
+                    self.outgoing(output)
+                        .iter()
+                        .map(move |input| (self[*input].node, *input, output))
+                })
+                .collect()
+        };
+
+        let mut paths = Vec::new();
+        if from == to {
+            paths.push(NodePath {
+                nodes: vec![from],
+                connections: Vec::new(),
+            });
+            return paths;
+        }
+
+        // Iterative depth-first search, like in `find_cycle`. The connection
+        // at `path[i]` goes from `stack[i]` to `stack[i + 1]`.
+        let mut stack = vec![(from, edges(from), 0)];
+        let mut path: Vec<(InputId, OutputId)> = Vec::new();
+        let mut on_path = HashSet::from([from]);
+        while let Some((_, node_edges, next)) = stack.last_mut() {
+            if let Some(&(next_node, input, output)) = node_edges.get(*next) {
+                *next += 1;
+                if next_node == to {
+                    let mut connections = path.clone();
+                    connections.push((input, output));
+                    let mut nodes: Vec<NodeId> = stack.iter().map(|(n, _, _)| *n).collect();
+                    nodes.push(to);
+                    paths.push(NodePath { nodes, connections });
+                } else if on_path.insert(next_node) {
+                    stack.push((next_node, edges(next_node), 0));
+                    path.push((input, output));
+                }
+            } else if let Some((node, _, _)) = stack.pop() {
+                on_path.remove(&node);
+                path.pop();
+            }
+        }
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    pub fn test_traversal() {
+        // a -> b -> d, a -> c -> d, and a cycle between d and e
+        let mut graph = TestGraph::new();
+        let [a, b, c, d, e] = [(); 5].map(|_| add_node(&mut graph, &["in1", "in2", "in3"]));
+        connect(&mut graph, a, b, "in1");
+        connect(&mut graph, a, c, "in1");
+        connect(&mut graph, b, d, "in1");
+        connect(&mut graph, c, d, "in2");
+        connect(&mut graph, d, e, "in1");
+        connect(&mut graph, e, d, "in3");
+
+        let downstream = |order| -> Vec<NodeId> {
+            graph
+                .traverse(a, TraversalDirection::Downstream, order)
+                .collect()
+        };
+        assert_eq!(downstream(TraversalOrder::BreadthFirst), vec![b, c, d, e]);
+        assert_eq!(downstream(TraversalOrder::DepthFirst), vec![b, d, e, c]);
+        let c_in = graph[c].get_input("in1").unwrap();
+        let upstream: Vec<NodeId> = graph
+            .traverse_from_param(c_in.into(), TraversalOrder::BreadthFirst)
+            .collect();
+        assert_eq!(upstream, vec![a]);
+
+        assert!(graph.is_reachable(a, e));
+        assert!(!graph.is_reachable(e, a));
+        assert_eq!(graph.sources(), vec![a]);
+        assert!(graph.sinks().is_empty());
+
+        let components = graph.strongly_connected_components();
+        assert_eq!(components.len(), 4);
+        assert_eq!(components[0], vec![a]);
+        let mut cycle = components[3].clone();
+        cycle.sort();
+        assert_eq!(cycle, vec![d, e]);
+
+        let paths = graph.all_paths(a, e);
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].nodes, vec![a, b, d, e]);
+        assert_eq!(paths[1].nodes, vec![a, c, d, e]);
+        assert_eq!(
+            paths[1].connections[1].0,
+            graph[d].get_input("in2").unwrap()
+        );
+    }
+}
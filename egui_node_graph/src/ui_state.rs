@@ -11,6 +11,21 @@ pub struct PanZoom {
     pub zoom: f32,
 }
 
+/// Decides how the editor treats connections that would introduce a cycle in
+/// the graph while the user is dragging them.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub enum CyclePolicy {
+    /// Cycle-forming connections are accepted like any other connection.
+    #[default]
+    Allow,
+    /// Cycle-forming connections are rejected.
+    Forbid,
+    /// User code decides, on a per-connection basis, using
+    /// [`NodeDataTrait::allow_cycle`].
+    AskUser,
+}
+
 #[derive(Clone)]
 #[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
 pub struct GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState> {
@@ -28,10 +43,38 @@ pub struct GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserSta
     pub ongoing_box_selection: Option<egui::Pos2>,
     /// The position of each node.
     pub node_positions: SecondaryMap<NodeId, egui::Pos2>,
+    /// The comment frames drawn behind the nodes.
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub frames: SlotMap<FrameId, CommentFrame>,
+    /// Frames are drawn in this order, below all the nodes. Frames that are
+    /// drawn last are on top of the others.
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub frame_order: Vec<FrameId>,
+    /// The knots the wires are rerouted through.
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub knots: WireKnots,
+    /// The id of each node in the interchange file the graph was loaded from.
+    /// Saving the graph again keeps these ids, see [`GraphEditorState::to_file`].
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub file_ids: SecondaryMap<NodeId, u32>,
     /// The node finder is used to create new nodes.
     pub node_finder: Option<NodeFinder<NodeTemplate>>,
     /// The panning of the graph viewport.
     pub pan_zoom: PanZoom,
+    /// Whether the user can create connections that introduce cycles.
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub cycle_policy: CyclePolicy,
+    /// The group nodes opened by the user, from the outermost one. The
+    /// editor displays the nested graph of the last one, or the root `graph`
+    /// when empty. The other fields of the editor state refer to the nodes of
+    /// the displayed graph, except for `graph` itself, which is always the
+    /// root graph.
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub open_groups: Vec<NodeId>,
+    /// The undo / redo history. Not persisted, a loaded graph starts with an
+    /// empty history.
+    #[cfg_attr(feature = "persistence", serde(skip, default = "UndoHistory::default"))]
+    pub history: UndoHistory<NodeData, DataType, ValueType>,
     pub _user_state: PhantomData<fn() -> UserState>,
 }
 
@@ -59,8 +102,15 @@ impl<NodeData, DataType, ValueType, NodeKind, UserState> Default
             selected_nodes: Default::default(),
             ongoing_box_selection: Default::default(),
             node_positions: Default::default(),
+            frames: Default::default(),
+            frame_order: Default::default(),
+            knots: Default::default(),
+            file_ids: Default::default(),
             node_finder: Default::default(),
             pan_zoom: Default::default(),
+            cycle_policy: Default::default(),
+            open_groups: Default::default(),
+            history: Default::default(),
             _user_state: Default::default(),
         }
     }
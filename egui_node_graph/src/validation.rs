@@ -0,0 +1,457 @@
+use super::*;
+use std::collections::HashSet;
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
+    /// Checks the consistency of the graph, which may be broken by editing
+    /// its fields directly, or by loading a corrupt file. Returns all the
+    /// problems found:
+    /// - Nodes listing parameters that are missing, or refer to another node.
+    /// - Parameters not listed by their node.
+    /// - Nodes with more than one input, or output, with the same name.
+    /// - Connections from or to missing parameters.
+    /// - Connections to [`InputParamKind::ConstantOnly`] inputs, and extra
+    ///   connections to [`InputCardinality::Single`] inputs.
+    /// - Connections between incompatible types, see
+    ///   [`DataTypeTrait::can_connect_to`].
+    /// - An index of outgoing connections not matching the connections.
+    /// - Groups of missing nodes, and group ports forwarding nested parameters
+    ///   of incompatible types. Ports referring to removed parameters are
+    ///   allowed, see [`NodeGroup`].
+    /// - The problems of the nested graphs of groups, including their node and
+    ///   frame orders, reported as [`EguiGraphError::InGroup`].
+    pub fn validate<UserState>(&self, user_state: &mut UserState) -> Result<(), Vec<EguiGraphError>>
+    where
+        DataType: DataTypeTrait<UserState>,
+    {
+        let mut errors = Vec::new();
+
+        for (node_id, node) in &self.nodes {
+            let mut names = HashSet::new();
+            for (name, input) in &node.inputs {
+                let param = AnyParameterId::Input(*input);
+                match self.inputs.get(*input) {
+                    None => errors.push(EguiGraphError::MissingParameter {
+                        node: node_id,
+                        param,
+                    }),
+                    Some(input) if input.node != node_id => {
+                        errors.push(EguiGraphError::WrongParameterNode {
+                            param,
+                            node: node_id,
+                            owner: input.node,
+                        })
+                    }
+                    Some(_) => {}
+                }
+                if !names.insert(name) {
+                    errors.push(EguiGraphError::DuplicateParameterName(
+                        node_id,
+                        name.clone(),
+                    ));
+                }
+            }
+
+            let mut names = HashSet::new();
+            for (name, output) in &node.outputs {
+                let param = AnyParameterId::Output(*output);
+                match self.outputs.get(*output) {
+                    None => errors.push(EguiGraphError::MissingParameter {
+                        node: node_id,
+                        param,
+                    }),
+                    Some(output) if output.node != node_id => {
+                        errors.push(EguiGraphError::WrongParameterNode {
+                            param,
+                            node: node_id,
+                            owner: output.node,
+                        })
+                    }
+                    Some(_) => {}
+                }
+                if !names.insert(name) {
+                    errors.push(EguiGraphError::DuplicateParameterName(
+                        node_id,
+                        name.clone(),
+                    ));
+                }
+            }
+        }
+
+        for (input_id, input) in &self.inputs {
+            let listed = self
+                .nodes
+                .get(input.node)
+                .is_some_and(|node| node.input_ids().any(|id| id == input_id));
+            if !listed {
+                errors.push(EguiGraphError::OrphanParameter(input_id.into()));
+            }
+        }
+        for (output_id, output) in &self.outputs {
+            let listed = self
+                .nodes
+                .get(output.node)
+                .is_some_and(|node| node.output_ids().any(|id| id == output_id));
+            if !listed {
+                errors.push(EguiGraphError::OrphanParameter(output_id.into()));
+            }
+        }
+
+        for (input_id, outputs) in &self.connections {
+            let Some(input) = self.inputs.get(input_id) else {
+                for output in outputs {
+                    errors.push(EguiGraphError::DanglingConnection {
+                        input: input_id,
+                        output: *output,
+                    });
+                }
+                continue;
+            };
+            if matches!(input.kind, InputParamKind::ConstantOnly) && !outputs.is_empty() {
+                errors.push(EguiGraphError::ConnectedConstantInput(input_id));
+            }
+            if input.cardinality == InputCardinality::Single && outputs.len() > 1 {
+                errors.push(EguiGraphError::TooManyConnections(input_id));
+            }
+            for output_id in outputs.iter().copied() {
+                match self.outputs.get(output_id) {
+                    None => errors.push(EguiGraphError::DanglingConnection {
+                        input: input_id,
+                        output: output_id,
+                    }),
+                    Some(output) if !output.typ.can_connect_to(&input.typ, user_state) => errors
+                        .push(EguiGraphError::TypeMismatch {
+                            input: input_id,
+                            output: output_id,
+                        }),
+                    Some(_) => {}
+                }
+            }
+        }
+
+        let mut expected: SecondaryMap<OutputId, Vec<InputId>> = SecondaryMap::new();
+        for (input, outputs) in &self.connections {
+            for output in outputs.iter().copied() {
+                if let Some(entry) = expected.entry(output) {
+                    entry.or_default().push(input);
+                }
+            }
+        }
+        let outputs: HashSet<OutputId> = expected
+            .keys()
+            .chain(self.outgoing_connections.keys())
+            .collect();
+        for output in outputs {
+            let mut expected = expected.get(output).cloned().unwrap_or_default();
+            let mut actual = self.outgoing(output).to_vec();
+            expected.sort();
+            actual.sort();
+            if expected != actual {
+                errors.push(EguiGraphError::InconsistentOutgoingConnections(output));
+            }
+        }
+
+        for (group_id, group) in &self.groups {
+            if !self.nodes.contains_key(group_id) {
+                errors.push(EguiGraphError::OrphanGroup(group_id));
+                continue;
+            }
+            self.validate_group(group_id, group, user_state, &mut errors);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Checks the ports and the nested graph of a group node.
+    fn validate_group<UserState>(
+        &self,
+        group_id: NodeId,
+        group: &NodeGroup<NodeData, DataType, ValueType>,
+        user_state: &mut UserState,
+        errors: &mut Vec<EguiGraphError>,
+    ) where
+        DataType: DataTypeTrait<UserState>,
+    {
+        let inner = &group.graph;
+        let mut port_error = |port: AnyParameterId, owner: NodeId, inner: AnyParameterId| {
+            if owner != group_id {
+                errors.push(EguiGraphError::WrongParameterNode {
+                    param: port,
+                    node: group_id,
+                    owner,
+                });
+            } else {
+                errors.push(EguiGraphError::GroupPortTypeMismatch {
+                    group: group_id,
+                    port,
+                    inner,
+                });
+            }
+        };
+        for (input, inner_inputs) in &group.inputs {
+            let Some(port) = self.inputs.get(input) else {
+                continue;
+            };
+            for inner_input in inner_inputs.iter().copied() {
+                let compatible = match inner.inputs.get(inner_input) {
+                    Some(param) => port.typ.can_connect_to(&param.typ, user_state),
+                    None => true,
+                };
+                if port.node != group_id || !compatible {
+                    port_error(input.into(), port.node, inner_input.into());
+                }
+            }
+        }
+        for (output, inner_output) in &group.outputs {
+            let Some(port) = self.outputs.get(output) else {
+                continue;
+            };
+            let compatible = match inner.outputs.get(*inner_output) {
+                Some(param) => param.typ.can_connect_to(&port.typ, user_state),
+                None => true,
+            };
+            if port.node != group_id || !compatible {
+                port_error(output.into(), port.node, (*inner_output).into());
+            }
+        }
+
+        let mut inner_errors = inner.validate(user_state).err().unwrap_or_default();
+        validate_layout(
+            inner,
+            &group.node_order,
+            &group.node_positions,
+            &group.frames,
+            &group.frame_order,
+            &mut inner_errors,
+        );
+        errors.extend(
+            inner_errors
+                .into_iter()
+                .map(|error| EguiGraphError::InGroup {
+                    group: group_id,
+                    error: Box::new(error),
+                }),
+        );
+    }
+}
+
+/// Checks the editor state kept next to a graph: Every node must appear once
+/// in the `node_order`, which can't contain unknown nodes, every node must
+/// have a position, and every frame must appear once in the `frame_order`,
+/// which can't contain unknown frames.
+fn validate_layout<NodeData, DataType, ValueType>(
+    graph: &Graph<NodeData, DataType, ValueType>,
+    node_order: &[NodeId],
+    node_positions: &SecondaryMap<NodeId, egui::Pos2>,
+    frames: &SlotMap<FrameId, CommentFrame>,
+    frame_order: &[FrameId],
+    errors: &mut Vec<EguiGraphError>,
+) {
+    let mut ordered = HashSet::new();
+    for node in node_order.iter().copied() {
+        if !graph.nodes.contains_key(node) || !ordered.insert(node) {
+            errors.push(EguiGraphError::InvalidNodeOrder(node));
+        }
+    }
+    for node in graph.iter_nodes() {
+        if !ordered.contains(&node) {
+            errors.push(EguiGraphError::MissingFromNodeOrder(node));
+        }
+        if !node_positions.contains_key(node) {
+            errors.push(EguiGraphError::MissingNodePosition(node));
+        }
+    }
+
+    let mut ordered = HashSet::new();
+    for frame in frame_order.iter().copied() {
+        if !frames.contains_key(frame) || !ordered.insert(frame) {
+            errors.push(EguiGraphError::InvalidFrameOrder(frame));
+        }
+    }
+    for frame in frames.keys() {
+        if !ordered.contains(&frame) {
+            errors.push(EguiGraphError::MissingFromFrameOrder(frame));
+        }
+    }
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+where
+    DataType: DataTypeTrait<UserState>,
+{
+    /// Checks the consistency of the editor state. In addition to the checks
+    /// of [`Graph::validate`], every node must appear once in the
+    /// `node_order`, which can't contain unknown nodes, and every node must
+    /// have a position. The same goes for the frames and the `frame_order`.
+    /// The `open_groups` must be a path of nested groups, starting from the
+    /// root graph.
+    pub fn validate(&self, user_state: &mut UserState) -> Result<(), Vec<EguiGraphError>> {
+        let mut errors = match self.graph.validate(user_state) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors,
+        };
+
+        validate_layout(
+            &self.graph,
+            &self.node_order,
+            &self.node_positions,
+            &self.frames,
+            &self.frame_order,
+            &mut errors,
+        );
+
+        let mut graph = &self.graph;
+        for group_id in self.open_groups.iter().copied() {
+            match graph.groups.get(group_id) {
+                Some(group) => graph = &group.graph,
+                None => {
+                    errors.push(EguiGraphError::InvalidOpenGroup(group_id));
+                    break;
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    pub fn test_validate() {
+        let mut graph = TestGraph::new();
+        let a = add_node(&mut graph, &["in"]);
+        let b = add_node(&mut graph, &["in"]);
+        let a_out = graph[a].get_output("out").unwrap();
+        let b_in = graph[b].get_input("in").unwrap();
+        graph.add_connection(a_out, b_in);
+        assert!(graph.validate(&mut ()).is_ok());
+
+        // Edit the fields directly, bypassing the graph methods
+        graph.inputs[b_in].typ = Type::Vec2;
+        graph.inputs[b_in].kind = InputParamKind::ConstantOnly;
+        graph.nodes[b].outputs[0].0 = "in".into();
+        graph.nodes[b].outputs.push(("in".into(), a_out));
+        let errors = graph.validate(&mut ()).unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                EguiGraphError::WrongParameterNode { node, owner, .. },
+                EguiGraphError::DuplicateParameterName(duplicate, _),
+                EguiGraphError::ConnectedConstantInput(constant),
+                EguiGraphError::TypeMismatch { input, output },
+            ] if *node == b && *owner == a && *duplicate == b && *constant == b_in
+                && *input == b_in && *output == a_out
+        ));
+    }
+
+    #[test]
+    pub fn test_validate_outgoing_connections() {
+        let mut graph = TestGraph::new();
+        let a = add_node(&mut graph, &["in"]);
+        let b = add_node(&mut graph, &["in"]);
+        let a_out = graph[a].get_output("out").unwrap();
+        let b_out = graph[b].get_output("out").unwrap();
+        let a_in = graph[a].get_input("in").unwrap();
+        let b_in = graph[b].get_input("in").unwrap();
+        graph.add_connection(a_out, b_in);
+
+        // The index lists a connection that doesn't exist, and misses one
+        graph.outgoing_connections.insert(b_out, vec![a_in]);
+        graph.connections[b_in].clear();
+        let errors = graph.validate(&mut ()).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        for output in [a_out, b_out] {
+            assert!(errors.iter().any(|error| matches!(
+                error,
+                EguiGraphError::InconsistentOutgoingConnections(o) if *o == output
+            )));
+        }
+    }
+
+    #[test]
+    pub fn test_validate_groups() {
+        let mut graph = TestGraph::new();
+        let a = add_node(&mut graph, &["in"]);
+        let b = add_node(&mut graph, &["in"]);
+        let a_out = graph[a].get_output("out").unwrap();
+        let b_in = graph[b].get_input("in").unwrap();
+        graph.add_connection(a_out, b_in);
+        let (group, inner_ids) = graph.group_nodes(&[b], "group".into(), ());
+        assert!(graph.validate(&mut ()).is_ok());
+
+        // The group input feeds a nested input of another type, and the
+        // nested graph lost the position of its node.
+        let group_in = graph[group].get_input("in").unwrap();
+        let inner_b = inner_ids[b];
+        let inner_in = graph.groups[group].graph[inner_b].get_input("in").unwrap();
+        graph.groups[group].graph.inputs[inner_in].typ = Type::Vec2;
+        graph.groups[group].node_positions.remove(inner_b);
+        let errors = graph.validate(&mut ()).unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                EguiGraphError::GroupPortTypeMismatch { group: g, port, inner },
+                EguiGraphError::InGroup { group: g2, error },
+            ] if *g == group && *port == AnyParameterId::Input(group_in)
+                && *inner == AnyParameterId::Input(inner_in) && *g2 == group
+                && matches!(**error, EguiGraphError::MissingNodePosition(node) if node == inner_b)
+        ));
+
+        // Ports referring to removed parameters are allowed
+        graph.groups[group].graph.inputs[inner_in].typ = Type::Float;
+        graph.groups[group]
+            .node_positions
+            .insert(inner_b, egui::Pos2::ZERO);
+        graph.remove_input_param(group_in);
+        assert!(graph.validate(&mut ()).is_ok());
+
+        graph.nodes.remove(group);
+        assert!(matches!(
+            graph.validate(&mut ()).unwrap_err().as_slice(),
+            [EguiGraphError::OrphanGroup(g)] if *g == group
+        ));
+    }
+
+    #[test]
+    pub fn test_validate_editor_state() {
+        let mut state = GraphEditorState::<(), Type, (), (), ()>::default();
+        let a = add_node(&mut state.graph, &["in"]);
+        state.node_order.push(a);
+        state.node_positions.insert(a, egui::Pos2::ZERO);
+        let frame = state.add_frame(CommentFrame::new(
+            "frame",
+            egui::Pos2::ZERO,
+            egui::Vec2::ZERO,
+        ));
+        let other = state.add_frame(CommentFrame::new(
+            "other",
+            egui::Pos2::ZERO,
+            egui::Vec2::ZERO,
+        ));
+        assert!(state.validate(&mut ()).is_ok());
+
+        state.frame_order = vec![frame, frame];
+        state.open_groups.push(a);
+        let errors = state.validate(&mut ()).unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                EguiGraphError::InvalidFrameOrder(repeated),
+                EguiGraphError::MissingFromFrameOrder(missing),
+                EguiGraphError::InvalidOpenGroup(group),
+            ] if *repeated == frame && *missing == other && *group == a
+        ));
+    }
+}
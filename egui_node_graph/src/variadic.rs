@@ -0,0 +1,225 @@
+use super::*;
+
+/// The name of the slot at `index` of the variadic input `name`.
+fn slot_name(name: &str, index: usize) -> String {
+    format!("{name} {}", index + 1)
+}
+
+/// A variadic input declared by a node template, see
+/// [`NodeTemplateTrait::variadic_inputs`]. The fields are the arguments of
+/// [`Graph::add_variadic_input_param`].
+#[derive(Debug, Clone)]
+pub struct VariadicInput<DataType, ValueType> {
+    pub name: String,
+    pub typ: DataType,
+    pub value: ValueType,
+    pub kind: InputParamKind,
+    pub shown_inline: bool,
+}
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType> {
+    /// Returns the slots of the variadic input `name` of `node_id`, in order.
+    /// See [`Graph::add_variadic_input_param`].
+    pub fn variadic_inputs(&self, node_id: NodeId, name: &str) -> Vec<InputId> {
+        self[node_id]
+            .input_ids()
+            .filter(|input| self[*input].variadic.as_deref() == Some(name))
+            .collect()
+    }
+
+    /// Adds a variadic input to `node_id`: A list of input parameters, or
+    /// slots, named `"{name} 1"`, `"{name} 2"`, etc. that grows as slots get
+    /// connected. There's always a single empty slot at the end of the list,
+    /// which spawns a new one when connected, and slots are removed again
+    /// when disconnected. See [`Graph::update_variadic_inputs`].
+    ///
+    /// Returns the first slot. All slots are created with the given settings.
+    /// Node templates can declare their variadic inputs instead, see
+    /// [`NodeTemplateTrait::variadic_inputs`].
+    pub fn add_variadic_input_param(
+        &mut self,
+        node_id: NodeId,
+        name: String,
+        typ: DataType,
+        value: ValueType,
+        kind: InputParamKind,
+        shown_inline: bool,
+    ) -> InputId {
+        let slot = slot_name(&name, 0);
+        let input_id = self.add_input_param(node_id, slot, typ, value, kind, shown_inline);
+        self.inputs[input_id].variadic = Some(name);
+        input_id
+    }
+
+    /// Adds a node of the given template: Its parameters are created by
+    /// [`NodeTemplateTrait::build_node`], followed by its
+    /// [`NodeTemplateTrait::variadic_inputs`].
+    pub fn add_template_node<NodeTemplate>(
+        &mut self,
+        template: &NodeTemplate,
+        user_state: &mut NodeTemplate::UserState,
+    ) -> NodeId
+    where
+        NodeTemplate:
+            NodeTemplateTrait<NodeData = NodeData, DataType = DataType, ValueType = ValueType>,
+    {
+        self.add_node(
+            template.node_graph_label(user_state),
+            template.user_data(user_state),
+            |graph, node_id| {
+                template.build_node(graph, user_state, node_id);
+                for input in template.variadic_inputs(user_state) {
+                    graph.add_variadic_input_param(
+                        node_id,
+                        input.name,
+                        input.typ,
+                        input.value,
+                        input.kind,
+                        input.shown_inline,
+                    );
+                }
+            },
+        )
+    }
+}
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType>
+where
+    DataType: Clone,
+    ValueType: Clone,
+{
+    /// Grows or shrinks the variadic inputs of `node_id` to match their
+    /// connections: The empty slots are removed, and a new empty slot is added
+    /// after the last one. The remaining slots are then renamed to keep their
+    /// numbering consecutive.
+    ///
+    /// [`GraphEditorState::update_variadic_inputs`] calls this when
+    /// connections are made or removed in the editor. User code modifying the
+    /// connections of variadic inputs should call it for the modified nodes.
+    pub fn update_variadic_inputs(&mut self, node_id: NodeId) {
+        let Some(node) = self.nodes.get(node_id) else {
+            return;
+        };
+        let mut names: Vec<String> = Vec::new();
+        for input in node.input_ids() {
+            if let Some(name) = &self[input].variadic {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+
+        for name in names {
+            let slots = self.variadic_inputs(node_id, &name);
+            let Some((last, rest)) = slots.split_last() else {
+                continue;
+            };
+            for slot in rest {
+                if self.input_connections(*slot).is_empty() {
+                    self.remove_input_param(*slot);
+                }
+            }
+            if !self.input_connections(*last).is_empty() {
+                let index = self[node_id]
+                    .inputs
+                    .iter()
+                    .position(|(_, input)| input == last)
+                    .expect("Slot should be an input of the node");
+                let param = self[*last].clone();
+                self.insert_input_param_like(node_id, index + 1, String::new(), param);
+            }
+
+            let slots = self.variadic_inputs(node_id, &name);
+            for (index, slot) in slots.iter().copied().enumerate() {
+                let slot_name = slot_name(&name, index);
+                let renamed = self[node_id]
+                    .inputs
+                    .iter()
+                    .any(|(param_name, input)| *input == slot && *param_name != slot_name);
+                if renamed {
+                    self.set_input_name(slot, slot_name);
+                }
+            }
+        }
+    }
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+where
+    DataType: Clone,
+    ValueType: Clone,
+{
+    /// Grows or shrinks the variadic inputs of the open graph after the
+    /// connections made or removed during the frame of `response`. Call it
+    /// with the response of [`GraphEditorState::draw_graph_editor`] when the
+    /// graph has variadic inputs.
+    pub fn update_variadic_inputs<UserResponse>(
+        &mut self,
+        response: &GraphResponse<UserResponse, NodeData>,
+    ) where
+        UserResponse: UserResponseTrait,
+        NodeData: NodeDataTrait,
+    {
+        self.with_open_group(|state| {
+            for node in response.reconnected_nodes(&state.graph) {
+                state.graph.update_variadic_inputs(node);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    pub fn test_variadic_inputs() {
+        let mut graph = TestGraph::new();
+        let source = add_node(&mut graph, &[]);
+        let output = graph[source].get_output("out").unwrap();
+        let concat = graph.add_node("concat".into(), (), |graph, node_id| {
+            graph.add_variadic_input_param(
+                node_id,
+                "in".into(),
+                Type::Float,
+                (),
+                InputParamKind::ConnectionOnly,
+                true,
+            );
+            graph.add_input_param(
+                node_id,
+                "separator".into(),
+                Type::Text,
+                (),
+                InputParamKind::ConstantOnly,
+                true,
+            );
+        });
+        let names = |graph: &TestGraph| -> Vec<String> {
+            graph[concat]
+                .inputs
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect()
+        };
+
+        // Connecting the trailing slot spawns a new one after it
+        for _ in 0..2 {
+            let last = *graph.variadic_inputs(concat, "in").last().unwrap();
+            graph.add_connection(output, last);
+            graph.update_variadic_inputs(concat);
+        }
+        assert_eq!(names(&graph), vec!["in 1", "in 2", "in 3", "separator"]);
+
+        // Disconnecting a slot removes it, and renumbers the others
+        let first = graph.variadic_inputs(concat, "in")[0];
+        graph.disconnect(output, first);
+        graph.update_variadic_inputs(concat);
+        assert_eq!(names(&graph), vec!["in 1", "in 2", "separator"]);
+        let slots = graph.variadic_inputs(concat, "in");
+        assert_eq!(graph.connection(slots[0]), Some(output));
+        assert_eq!(graph.connection(slots[1]), None);
+    }
+}
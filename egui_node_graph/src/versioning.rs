@@ -0,0 +1,330 @@
+use super::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+/// The version of the serialized format of this crate's own types, like the
+/// [`Graph`] or the [`GraphEditorState`]. Files written with an older version
+/// are migrated when loaded with [`GraphEditorState::load_versioned`].
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The two formats a saved editor state is versioned with. See
+/// [`GraphEditorState::load_versioned`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SavedFormat {
+    /// The format of this crate's own types, see [`FORMAT_VERSION`].
+    Crate,
+    /// The format of the application's types, see [`Migrations`].
+    App,
+}
+
+impl std::fmt::Display for SavedFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SavedFormat::Crate => f.write_str("egui_node_graph format"),
+            SavedFormat::App => f.write_str("application format"),
+        }
+    }
+}
+
+/// A migration of a serialized editor state from one version to the next. It
+/// receives the state as a JSON value, and edits it in place.
+pub type Migration = Box<dyn Fn(&mut Value) -> Result<(), String>>;
+
+/// The migrations of this crate's format. The migration at index `i` takes a
+/// state of version `i` to version `i + 1`.
+fn format_migrations() -> Vec<Migration> {
+    vec![
+        // Version 0 covers the files written before the format was versioned,
+        // which are saved without an envelope. The fields added since then
+        // are filled with their defaults, so no changes are needed.
+        Box::new(|_| Ok(())),
+    ]
+}
+
+/// The envelope of a saved editor state: The state itself, together with the
+/// versions of the formats it was written with.
+#[derive(Serialize, Deserialize)]
+struct VersionedState<T> {
+    format_version: u32,
+    app_version: u32,
+    state: T,
+}
+
+/// The chain of migrations of the application's own format, for the types it
+/// stores in the graph, like `NodeData` or `ValueType`. Applications should
+/// increase their version every time they change these types in a way that
+/// breaks their serialized form, and register a migration from the previous
+/// version.
+pub struct Migrations {
+    app_version: u32,
+    migrations: Vec<(u32, Migration)>,
+}
+
+impl Migrations {
+    /// Creates an empty chain of migrations, for the current `app_version`.
+    pub fn new(app_version: u32) -> Self {
+        Self {
+            app_version,
+            migrations: Vec::new(),
+        }
+    }
+
+    /// The current version of the application's format.
+    pub fn app_version(&self) -> u32 {
+        self.app_version
+    }
+
+    /// Registers the migration from `version` to `version + 1`. Versions
+    /// without a migration are assumed to be compatible with the next one.
+    ///
+    /// The migration receives the whole state as a JSON value, after the
+    /// migrations of this crate's format have been applied.
+    pub fn with_migration(
+        mut self,
+        version: u32,
+        migration: impl Fn(&mut Value) -> Result<(), String> + 'static,
+    ) -> Self {
+        self.migrations.push((version, Box::new(migration)));
+        self
+    }
+
+    /// Applies the migrations needed to bring a state from the given versions
+    /// to the current ones.
+    fn migrate(
+        &self,
+        value: &mut Value,
+        format_version: u32,
+        app_version: u32,
+    ) -> Result<(), EguiGraphError> {
+        if format_version > FORMAT_VERSION {
+            return Err(EguiGraphError::NewerVersion {
+                format: SavedFormat::Crate,
+                found: format_version,
+                supported: FORMAT_VERSION,
+            });
+        }
+        if app_version > self.app_version {
+            return Err(EguiGraphError::NewerVersion {
+                format: SavedFormat::App,
+                found: app_version,
+                supported: self.app_version,
+            });
+        }
+
+        let format_migrations = format_migrations();
+        for version in format_version..FORMAT_VERSION {
+            format_migrations[version as usize](value).map_err(|message| {
+                EguiGraphError::Migration {
+                    format: SavedFormat::Crate,
+                    version,
+                    message,
+                }
+            })?;
+        }
+        for version in app_version..self.app_version {
+            for (_, migration) in self.migrations.iter().filter(|(v, _)| *v == version) {
+                migration(value).map_err(|message| EguiGraphError::Migration {
+                    format: SavedFormat::App,
+                    version,
+                    message,
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+{
+    /// Serializes the editor state to JSON text, in an envelope recording the
+    /// current [`FORMAT_VERSION`] and the `app_version` of the application's
+    /// own format. See [`GraphEditorState::load_versioned`].
+    pub fn save_versioned(&self, app_version: u32) -> Result<String, EguiGraphError>
+    where
+        Self: Serialize,
+    {
+        let envelope = VersionedState {
+            format_version: FORMAT_VERSION,
+            app_version,
+            state: self,
+        };
+        serde_json::to_string(&envelope).map_err(|err| EguiGraphError::Persistence(err.to_string()))
+    }
+
+    /// Loads an editor state saved with [`GraphEditorState::save_versioned`],
+    /// possibly by an older release of this crate or of the application. The
+    /// state is migrated to the current formats before being deserialized:
+    /// First with the migrations of this crate, then with `migrations`.
+    ///
+    /// Plain JSON states, saved without an envelope, are loaded as version 0
+    /// of both formats.
+    pub fn load_versioned(text: &str, migrations: &Migrations) -> Result<Self, EguiGraphError>
+    where
+        Self: DeserializeOwned,
+    {
+        let value: Value = serde_json::from_str(text)
+            .map_err(|err| EguiGraphError::Persistence(err.to_string()))?;
+        let is_envelope = value
+            .as_object()
+            .is_some_and(|object| object.contains_key("format_version"));
+        let mut envelope: VersionedState<Value> = if is_envelope {
+            serde_json::from_value(value)
+                .map_err(|err| EguiGraphError::Persistence(err.to_string()))?
+        } else {
+            VersionedState {
+                format_version: 0,
+                app_version: 0,
+                state: value,
+            }
+        };
+
+        migrations.migrate(
+            &mut envelope.state,
+            envelope.format_version,
+            envelope.app_version,
+        )?;
+        serde_json::from_value(envelope.state)
+            .map_err(|err| EguiGraphError::Persistence(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An editor state saved by the first release of the format, before it
+    /// was versioned: Inputs have a single connection, and there are no
+    /// frames, knots or groups.
+    const LEGACY_STATE: &str = r#"{
+        "graph": {
+            "nodes": [
+                {"value": null, "version": 0},
+                {"value": {"id": {"idx": 1, "version": 1}, "label": "a", "inputs": [],
+                    "outputs": [["out", {"idx": 1, "version": 1}]], "user_data": "data"},
+                    "version": 1},
+                {"value": {"id": {"idx": 2, "version": 1}, "label": "b",
+                    "inputs": [["in", {"idx": 1, "version": 1}]], "outputs": [],
+                    "user_data": "data"}, "version": 1}
+            ],
+            "inputs": [
+                {"value": null, "version": 0},
+                {"value": {"id": {"idx": 1, "version": 1}, "typ": null, "value": 1.0,
+                    "kind": "ConnectionOrConstant", "node": {"idx": 2, "version": 1},
+                    "shown_inline": true}, "version": 1}
+            ],
+            "outputs": [
+                {"value": null, "version": 0},
+                {"value": {"id": {"idx": 1, "version": 1}, "node": {"idx": 1, "version": 1},
+                    "typ": null}, "version": 1}
+            ],
+            "connections": [
+                {"value": null, "version": 0},
+                {"value": {"idx": 1, "version": 1}, "version": 1}
+            ]
+        },
+        "node_order": [{"idx": 1, "version": 1}, {"idx": 2, "version": 1}],
+        "connection_in_progress": null,
+        "selected_nodes": [],
+        "ongoing_box_selection": null,
+        "node_positions": [
+            {"value": null, "version": 0},
+            {"value": {"x": 0.0, "y": 0.0}, "version": 1},
+            {"value": {"x": 100.0, "y": 0.0}, "version": 1}
+        ],
+        "node_finder": null,
+        "pan_zoom": {"pan": {"x": 0.0, "y": 0.0}, "zoom": 0.0},
+        "_user_state": null
+    }"#;
+
+    type TestState = GraphEditorState<String, (), f32, (), ()>;
+
+    fn test_state() -> TestState {
+        let mut state = TestState::default();
+        let node = state
+            .graph
+            .add_node("node".into(), "data".into(), |graph, node_id| {
+                graph.add_input_param(
+                    node_id,
+                    "in".into(),
+                    (),
+                    1.0,
+                    InputParamKind::ConstantOnly,
+                    true,
+                );
+            });
+        state.node_order.push(node);
+        state.node_positions.insert(node, egui::pos2(1.0, 2.0));
+        state
+    }
+
+    #[test]
+    pub fn test_migrations() {
+        let text = test_state().save_versioned(1).unwrap();
+
+        // The app renamed its node data in version 2
+        let migrations = Migrations::new(2).with_migration(1, |state| {
+            for node in state["graph"]["nodes"].as_array_mut().unwrap() {
+                if let Some(node) = node.get_mut("value").filter(|n| !n.is_null()) {
+                    node["user_data"] = Value::from("migrated");
+                }
+            }
+            Ok(())
+        });
+        let loaded = TestState::load_versioned(&text, &migrations).unwrap();
+        let node = loaded.node_order[0];
+        assert_eq!(loaded.graph[node].user_data, "migrated");
+        assert_eq!(loaded.node_positions[node], egui::pos2(1.0, 2.0));
+
+        // Files from the future are rejected
+        assert!(matches!(
+            TestState::load_versioned(&text, &Migrations::new(0)),
+            Err(EguiGraphError::NewerVersion {
+                format: SavedFormat::App,
+                found: 1,
+                supported: 0
+            })
+        ));
+        let future = text.replace("\"format_version\":1", "\"format_version\":99");
+        assert!(matches!(
+            TestState::load_versioned(&future, &Migrations::new(1)),
+            Err(EguiGraphError::NewerVersion {
+                format: SavedFormat::Crate,
+                found: 99,
+                ..
+            })
+        ));
+
+        // Failing migrations report the version they start from
+        let failing = Migrations::new(2).with_migration(1, |_| Err("broken".into()));
+        assert!(matches!(
+            TestState::load_versioned(&text, &failing),
+            Err(EguiGraphError::Migration {
+                format: SavedFormat::App,
+                version: 1,
+                ..
+            })
+        ));
+
+        // Files without an envelope are version 0
+        let plain = serde_json::to_string(&test_state()).unwrap();
+        let loaded = TestState::load_versioned(&plain, &Migrations::new(0)).unwrap();
+        assert_eq!(loaded.graph.nodes.len(), 1);
+    }
+
+    #[test]
+    pub fn test_legacy_state() {
+        let loaded = TestState::load_versioned(LEGACY_STATE, &Migrations::new(0)).unwrap();
+        let (a, b) = (loaded.node_order[0], loaded.node_order[1]);
+        let output = loaded.graph[a].get_output("out").unwrap();
+        let input = loaded.graph[b].get_input("in").unwrap();
+        assert_eq!(loaded.graph.input_connections(input), &[output]);
+        assert_eq!(loaded.graph.outgoing(output), &[input]);
+        assert_eq!(loaded.graph[input].cardinality, InputCardinality::Single);
+        assert_eq!(loaded.node_positions[b], egui::pos2(100.0, 0.0));
+        assert!(loaded.frames.is_empty());
+        assert!(loaded.knots.is_empty());
+        assert!(loaded.graph.groups.is_empty());
+        assert!(!loaded.graph[a].bypassed);
+    }
+}
@@ -0,0 +1,234 @@
+use super::*;
+use std::collections::HashSet;
+
+impl<NodeData, DataType, ValueType> Graph<NodeData, DataType, ValueType>
+where
+    DataType: Clone + PartialEq,
+{
+    /// Like [`Graph::add_input_param`], but the created parameter is a
+    /// wildcard, of the unresolved type `typ`. The wildcard parameters of
+    /// this node with the same `link` always share the same type. See
+    /// [`Wildcard`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_wildcard_input_param(
+        &mut self,
+        node_id: NodeId,
+        name: String,
+        typ: DataType,
+        value: ValueType,
+        kind: InputParamKind,
+        shown_inline: bool,
+        link: usize,
+    ) -> InputId {
+        let unresolved = typ.clone();
+        let input_id = self.add_input_param(node_id, name, typ, value, kind, shown_inline);
+        self.inputs[input_id].wildcard = Some(Wildcard { unresolved, link });
+        input_id
+    }
+
+    /// Like [`Graph::add_output_param`], but the created parameter is a
+    /// wildcard, of the unresolved type `typ`. See
+    /// [`Graph::add_wildcard_input_param`].
+    pub fn add_wildcard_output_param(
+        &mut self,
+        node_id: NodeId,
+        name: String,
+        typ: DataType,
+        link: usize,
+    ) -> OutputId {
+        let unresolved = typ.clone();
+        let output_id = self.add_output_param(node_id, name, typ);
+        self.outputs[output_id].wildcard = Some(Wildcard { unresolved, link });
+        output_id
+    }
+
+    /// Updates the type of the wildcard parameters of `node_id`, and of the
+    /// wildcard parameters whose type depends on them. Wildcards connected to
+    /// each other, or linked inside a node, take the type of the first
+    /// regular parameter connected to any of them, or revert to their
+    /// unresolved type when there's none. Returns the parameters whose type
+    /// changed, and marks their nodes as dirty.
+    ///
+    /// [`GraphEditorState::update_wildcard_types`] calls this when connections
+    /// are made or removed in the editor. User code modifying the connections
+    /// of wildcard parameters should call it for the nodes at both ends of the
+    /// modified connections.
+    pub fn infer_wildcard_types(&mut self, node_id: NodeId) -> Vec<AnyParameterId> {
+        let mut changed = Vec::new();
+        let Some(node) = self.nodes.get(node_id) else {
+            return changed;
+        };
+        let params: Vec<AnyParameterId> = node
+            .input_ids()
+            .map(AnyParameterId::Input)
+            .chain(node.output_ids().map(AnyParameterId::Output))
+            .filter(|param| self.wildcard(*param).is_some())
+            .collect();
+
+        let mut visited = HashSet::new();
+        for param in params {
+            if visited.contains(&param) {
+                continue;
+            }
+            let (component, resolved) = self.wildcard_component(param, &mut visited);
+            for param in component {
+                let typ = match &resolved {
+                    Some(typ) => typ.clone(),
+                    None => self.wildcard(param).unwrap().unresolved.clone(),
+                };
+                let (node, current) = match param {
+                    AnyParameterId::Input(input) => {
+                        let param = &mut self.inputs[input];
+                        (param.node, &mut param.typ)
+                    }
+                    AnyParameterId::Output(output) => {
+                        let param = &mut self.outputs[output];
+                        (param.node, &mut param.typ)
+                    }
+                };
+                if *current != typ {
+                    *current = typ;
+                    self.mark_dirty(node);
+                    self.notify(GraphEvent::ParamChanged(param));
+                    changed.push(param);
+                }
+            }
+        }
+        changed
+    }
+
+    /// Finds the wildcard parameters whose type depends on the one of
+    /// `start`, and the type they resolve to, if any.
+    fn wildcard_component(
+        &self,
+        start: AnyParameterId,
+        visited: &mut HashSet<AnyParameterId>,
+    ) -> (Vec<AnyParameterId>, Option<DataType>) {
+        let mut component = Vec::new();
+        let mut resolved = None;
+        let mut stack = vec![start];
+        visited.insert(start);
+        while let Some(param) = stack.pop() {
+            component.push(param);
+            let link = self.wildcard(param).unwrap().link;
+            let (node, connected): (NodeId, Vec<AnyParameterId>) = match param {
+                AnyParameterId::Input(input) => (
+                    self[input].node,
+                    self.input_connections(input)
+                        .iter()
+                        .map(|output| AnyParameterId::Output(*output))
+                        .collect(),
+                ),
+                AnyParameterId::Output(output) => (
+                    self[output].node,
+                    self.outgoing(output)
+                        .iter()
+                        .map(|input| AnyParameterId::Input(*input))
+                        .collect(),
+                ),
+            };
+            let linked = self[node]
+                .input_ids()
+                .map(AnyParameterId::Input)
+                .chain(self[node].output_ids().map(AnyParameterId::Output))
+                .filter(|other| self.wildcard(*other).is_some_and(|w| w.link == link));
+
+            for other in linked.chain(connected) {
+                if self.wildcard(other).is_some() {
+                    if visited.insert(other) {
+                        stack.push(other);
+                    }
+                } else if resolved.is_none() {
+                    resolved = self.any_param_type(other).ok().cloned();
+                }
+            }
+        }
+        (component, resolved)
+    }
+
+    fn wildcard(&self, param: AnyParameterId) -> Option<&Wildcard<DataType>> {
+        match param {
+            AnyParameterId::Input(input) => self.inputs.get(input)?.wildcard.as_ref(),
+            AnyParameterId::Output(output) => self.outputs.get(output)?.wildcard.as_ref(),
+        }
+    }
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+where
+    DataType: Clone + PartialEq,
+{
+    /// Infers the types of the wildcard parameters of the open graph after
+    /// the connections made or removed during the frame of `response`. Call it
+    /// with the response of [`GraphEditorState::draw_graph_editor`] when the
+    /// graph has wildcard parameters.
+    pub fn update_wildcard_types<UserResponse>(
+        &mut self,
+        response: &GraphResponse<UserResponse, NodeData>,
+    ) where
+        UserResponse: UserResponseTrait,
+        NodeData: NodeDataTrait,
+    {
+        self.with_open_group(|state| {
+            for node in response.reconnected_nodes(&state.graph) {
+                state.graph.infer_wildcard_types(node);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn add_wildcard_node(graph: &mut TestGraph) -> NodeId {
+        graph.add_node("wildcard".into(), (), |graph, node_id| {
+            graph.add_wildcard_input_param(
+                node_id,
+                "in".into(),
+                Type::Any,
+                (),
+                InputParamKind::ConnectionOnly,
+                true,
+                0,
+            );
+            graph.add_wildcard_output_param(node_id, "out".into(), Type::Any, 0);
+        })
+    }
+
+    /// Connects `from` to `to` and infers the types of their wildcards.
+    fn connect_and_infer(graph: &mut TestGraph, from: NodeId, to: NodeId) {
+        connect(graph, from, to, "in");
+        graph.infer_wildcard_types(from);
+        graph.infer_wildcard_types(to);
+    }
+
+    fn output_type(graph: &TestGraph, node: NodeId) -> Type {
+        graph[graph[node].get_output("out").unwrap()].typ
+    }
+
+    #[test]
+    pub fn test_wildcard_inference() {
+        let mut graph = TestGraph::new();
+        let float = add_node(&mut graph, &["in"]);
+        let a = add_wildcard_node(&mut graph);
+        let b = add_wildcard_node(&mut graph);
+
+        // Types propagate through linked and connected wildcards
+        connect_and_infer(&mut graph, a, b);
+        assert_eq!(output_type(&graph, b), Type::Any);
+        connect_and_infer(&mut graph, float, a);
+        assert_eq!(output_type(&graph, a), Type::Float);
+        assert_eq!(output_type(&graph, b), Type::Float);
+
+        // And revert once disconnected
+        let output = graph[float].get_output("out").unwrap();
+        let input = graph[a].get_input("in").unwrap();
+        graph.disconnect(output, input);
+        graph.infer_wildcard_types(a);
+        assert_eq!(output_type(&graph, a), Type::Any);
+        assert_eq!(output_type(&graph, b), Type::Any);
+    }
+}
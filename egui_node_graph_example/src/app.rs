@@ -1,4 +1,4 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::borrow::Cow;
 
 use eframe::egui::{self, DragValue, TextStyle};
 use egui_node_graph::*;
@@ -8,6 +8,7 @@ use egui_node_graph::*;
 /// The NodeData holds a custom data struct inside each node. It's useful to
 /// store additional information that doesn't live in parameters. For this
 /// example, the node data stores the template (i.e. the "type") of the node.
+#[derive(Clone)]
 #[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub struct MyNodeData {
     template: MyNodeTemplate,
@@ -16,7 +17,7 @@ pub struct MyNodeData {
 /// `DataType`s are what defines the possible range of connections when
 /// attaching two ports together. The graph UI will make sure to not allow
 /// attaching incompatible datatypes.
-#[derive(PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub enum MyDataType {
     Scalar,
@@ -30,7 +31,7 @@ pub enum MyDataType {
 /// this library makes no attempt to check this consistency. For instance, it is
 /// up to the user code in this example to make sure no parameter is created
 /// with a DataType of Scalar and a ValueType of Vec2.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub enum MyValueType {
     Vec2 { value: egui::Vec2 },
@@ -88,6 +89,8 @@ pub enum MyNodeTemplate {
 pub enum MyResponse {
     SetActiveNode(NodeId),
     ClearActiveNode,
+    /// An inline widget modified the value of its parameter.
+    ValueChanged,
 }
 
 /// The graph 'global' state. This state struct is passed around to the node and
@@ -256,6 +259,19 @@ impl NodeTemplateTrait for MyNodeTemplate {
             }
         }
     }
+
+    fn conversion_node(
+        source: &MyDataType,
+        target: &MyDataType,
+        _user_state: &mut Self::UserState,
+    ) -> Option<Self> {
+        // Dropping a scalar onto a vector input inserts a "New vector" node,
+        // with the scalar connected to its first component.
+        match (source, target) {
+            (MyDataType::Scalar, MyDataType::Vec2) => Some(MyNodeTemplate::MakeVector),
+            _ => None,
+        }
+    }
 }
 
 pub struct AllMyNodeTemplates;
@@ -292,29 +308,41 @@ impl WidgetValueTrait for MyValueType {
     ) -> Vec<MyResponse> {
         // This trait is used to tell the library which UI to display for the
         // inline parameter widgets.
-        match self {
+        let changed = match self {
             MyValueType::Vec2 { value } => {
                 ui.label(param_name);
                 ui.horizontal(|ui| {
                     ui.label("x");
-                    ui.add(DragValue::new(&mut value.x));
+                    let x = ui.add(DragValue::new(&mut value.x));
                     ui.label("y");
-                    ui.add(DragValue::new(&mut value.y));
-                });
+                    let y = ui.add(DragValue::new(&mut value.y));
+                    x.changed() || y.changed()
+                })
+                .inner
             }
             MyValueType::Scalar { value } => {
                 ui.horizontal(|ui| {
                     ui.label(param_name);
-                    ui.add(DragValue::new(value));
-                });
+                    ui.add(DragValue::new(value)).changed()
+                })
+                .inner
             }
-        }
+        };
         // This allows you to return your responses from the inline widgets.
-        Vec::new()
+        // Reporting the modified values lets the editor undo them.
+        if changed {
+            vec![MyResponse::ValueChanged]
+        } else {
+            Vec::new()
+        }
     }
 }
 
-impl UserResponseTrait for MyResponse {}
+impl UserResponseTrait for MyResponse {
+    fn is_value_changed(&self) -> bool {
+        matches!(self, MyResponse::ValueChanged)
+    }
+}
 impl NodeDataTrait for MyNodeData {
     type Response = MyResponse;
     type UserState = MyGraphState;
@@ -379,6 +407,10 @@ pub struct NodeGraphExample {
     state: MyEditorState,
 
     user_state: MyGraphState,
+
+    // The results of the last evaluation. Kept across frames so that only
+    // the nodes affected by an edit need to be evaluated again.
+    outputs_cache: OutputsCache<MyValueType>,
 }
 
 #[cfg(feature = "persistence")]
@@ -396,6 +428,7 @@ impl NodeGraphExample {
         Self {
             state,
             user_state: MyGraphState::default(),
+            outputs_cache: OutputsCache::default(),
         }
     }
 }
@@ -413,16 +446,54 @@ impl eframe::App for NodeGraphExample {
         egui::TopBottomPanel::top("top").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 egui::widgets::global_dark_light_mode_switch(ui);
+                // `update_history` records an undo step for every edit made
+                // through the UI, and handles Ctrl+Z and Ctrl+Shift+Z. These
+                // buttons just expose the same functionality.
+                if ui
+                    .add_enabled(self.state.history.can_undo(), egui::Button::new("Undo"))
+                    .clicked()
+                {
+                    self.state.undo();
+                }
+                if ui
+                    .add_enabled(self.state.history.can_redo(), egui::Button::new("Redo"))
+                    .clicked()
+                {
+                    self.state.redo();
+                }
+                // Frames document regions of the graph. They are created in
+                // the middle of the view, and can be moved and resized there.
+                // Edits made outside of the editor are wrapped in a
+                // transaction, so they can be undone too.
+                if ui.button("Add frame").clicked() {
+                    let center = ctx.screen_rect().center() - self.state.pan_zoom.pan;
+                    self.state
+                        .transaction(HistoryStep::User("Add frame".into()), |state| {
+                            state.add_frame(CommentFrame::new(
+                                "Frame",
+                                center - egui::vec2(150.0, 100.0),
+                                egui::vec2(300.0, 200.0),
+                            ))
+                        });
+                }
             });
         });
         let graph_response = egui::CentralPanel::default()
             .show(ctx, |ui| {
-                self.state.draw_graph_editor(
+                // Copy and paste need the node types to be serializable.
+                #[cfg(feature = "persistence")]
+                let prepend_responses = self.state.handle_clipboard(ui, &mut self.user_state);
+                #[cfg(not(feature = "persistence"))]
+                let prepend_responses = Vec::default();
+                let graph_response = self.state.draw_graph_editor(
                     ui,
                     AllMyNodeTemplates,
                     &mut self.user_state,
-                    Vec::default(),
-                )
+                    prepend_responses,
+                );
+                // Makes the edits of this frame undoable.
+                self.state.update_history(ui, &graph_response);
+                graph_response
             })
             .inner;
         for node_response in graph_response.node_responses {
@@ -433,13 +504,20 @@ impl eframe::App for NodeGraphExample {
                 match user_event {
                     MyResponse::SetActiveNode(node) => self.user_state.active_node = Some(node),
                     MyResponse::ClearActiveNode => self.user_state.active_node = None,
+                    MyResponse::ValueChanged => {}
                 }
             }
         }
 
+        // The graph keeps track of the nodes modified since the last frame,
+        // discard their previous results.
+        let dirty_nodes = self.state.graph.take_dirty_nodes();
+        self.outputs_cache
+            .invalidate(&self.state.graph, &dirty_nodes);
+
         if let Some(node) = self.user_state.active_node {
             if self.state.graph.nodes.contains_key(node) {
-                let text = match evaluate_node(&self.state.graph, node, &mut HashMap::new()) {
+                let text = match evaluate_node(&self.state.graph, node, &mut self.outputs_cache) {
                     Ok(value) => format!("The result is: {:?}", value),
                     Err(err) => format!("Execution error: {}", err),
                 };
@@ -457,153 +535,62 @@ impl eframe::App for NodeGraphExample {
     }
 }
 
-type OutputsCache = HashMap<OutputId, MyValueType>;
+// The library evaluates the nodes connected to the inputs first, caches the
+// computed outputs and takes the inline value for unconnected inputs. We only
+// need to describe how each node computes its outputs from its inputs.
+impl NodeEvaluatorTrait for MyNodeData {
+    type DataType = MyDataType;
+    type ValueType = MyValueType;
+    type Value = MyValueType;
+    type EvalState = ();
 
-/// Recursively evaluates all dependencies of this node, then evaluates the node itself.
-pub fn evaluate_node(
-    graph: &MyGraph,
-    node_id: NodeId,
-    outputs_cache: &mut OutputsCache,
-) -> anyhow::Result<MyValueType> {
-    // To solve a similar problem as creating node types above, we define an
-    // Evaluator as a convenience. It may be overkill for this small example,
-    // but something like this makes the code much more readable when the
-    // number of nodes starts growing.
-
-    struct Evaluator<'a> {
-        graph: &'a MyGraph,
-        outputs_cache: &'a mut OutputsCache,
-        node_id: NodeId,
-    }
-    impl<'a> Evaluator<'a> {
-        fn new(graph: &'a MyGraph, outputs_cache: &'a mut OutputsCache, node_id: NodeId) -> Self {
-            Self {
-                graph,
-                outputs_cache,
-                node_id,
-            }
-        }
-        fn evaluate_input(&mut self, name: &str) -> anyhow::Result<MyValueType> {
-            // Calling `evaluate_input` recursively evaluates other nodes in the
-            // graph until the input value for a paramater has been computed.
-            evaluate_input(self.graph, self.node_id, name, self.outputs_cache)
-        }
-        fn populate_output(
-            &mut self,
-            name: &str,
-            value: MyValueType,
-        ) -> anyhow::Result<MyValueType> {
-            // After computing an output, we don't just return it, but we also
-            // populate the outputs cache with it. This ensures the evaluation
-            // only ever computes an output once.
-            //
-            // The return value of the function is the "final" output of the
-            // node, the thing we want to get from the evaluation. The example
-            // would be slightly more contrived when we had multiple output
-            // values, as we would need to choose which of the outputs is the
-            // one we want to return. Other outputs could be used as
-            // intermediate values.
-            //
-            // Note that this is just one possible semantic interpretation of
-            // the graphs, you can come up with your own evaluation semantics!
-            populate_output(self.graph, self.outputs_cache, self.node_id, name, value)
-        }
-        fn input_vector(&mut self, name: &str) -> anyhow::Result<egui::Vec2> {
-            self.evaluate_input(name)?.try_to_vec2()
-        }
-        fn input_scalar(&mut self, name: &str) -> anyhow::Result<f32> {
-            self.evaluate_input(name)?.try_to_scalar()
-        }
-        fn output_vector(&mut self, name: &str, value: egui::Vec2) -> anyhow::Result<MyValueType> {
-            self.populate_output(name, MyValueType::Vec2 { value })
-        }
-        fn output_scalar(&mut self, name: &str, value: f32) -> anyhow::Result<MyValueType> {
-            self.populate_output(name, MyValueType::Scalar { value })
-        }
-    }
+    fn evaluate(
+        &self,
+        _node_id: NodeId,
+        _graph: &MyGraph,
+        inputs: &NodeInputs<MyValueType>,
+        _state: &mut (),
+    ) -> Result<NodeOutputs<MyValueType>, NodeEvalError> {
+        // Type errors are reported together with the name of the parameter.
+        let input_scalar = |name: &str| {
+            inputs
+                .get(name)?
+                .try_to_scalar()
+                .map_err(|err| NodeEvalError::custom(err.to_string()).with_param(name))
+        };
+        let input_vector = |name: &str| {
+            inputs
+                .get(name)?
+                .try_to_vec2()
+                .map_err(|err| NodeEvalError::custom(err.to_string()).with_param(name))
+        };
+        let scalar = |value| NodeOutputs::new().with("out", MyValueType::Scalar { value });
+        let vector = |value| NodeOutputs::new().with("out", MyValueType::Vec2 { value });
 
-    let node = &graph[node_id];
-    let mut evaluator = Evaluator::new(graph, outputs_cache, node_id);
-    match node.user_data.template {
-        MyNodeTemplate::AddScalar => {
-            let a = evaluator.input_scalar("A")?;
-            let b = evaluator.input_scalar("B")?;
-            evaluator.output_scalar("out", a + b)
-        }
-        MyNodeTemplate::SubtractScalar => {
-            let a = evaluator.input_scalar("A")?;
-            let b = evaluator.input_scalar("B")?;
-            evaluator.output_scalar("out", a - b)
-        }
-        MyNodeTemplate::VectorTimesScalar => {
-            let scalar = evaluator.input_scalar("scalar")?;
-            let vector = evaluator.input_vector("vector")?;
-            evaluator.output_vector("out", vector * scalar)
-        }
-        MyNodeTemplate::AddVector => {
-            let v1 = evaluator.input_vector("v1")?;
-            let v2 = evaluator.input_vector("v2")?;
-            evaluator.output_vector("out", v1 + v2)
-        }
-        MyNodeTemplate::SubtractVector => {
-            let v1 = evaluator.input_vector("v1")?;
-            let v2 = evaluator.input_vector("v2")?;
-            evaluator.output_vector("out", v1 - v2)
-        }
-        MyNodeTemplate::MakeVector => {
-            let x = evaluator.input_scalar("x")?;
-            let y = evaluator.input_scalar("y")?;
-            evaluator.output_vector("out", egui::vec2(x, y))
-        }
-        MyNodeTemplate::MakeScalar => {
-            let value = evaluator.input_scalar("value")?;
-            evaluator.output_scalar("out", value)
-        }
+        Ok(match self.template {
+            MyNodeTemplate::AddScalar => scalar(input_scalar("A")? + input_scalar("B")?),
+            MyNodeTemplate::SubtractScalar => scalar(input_scalar("A")? - input_scalar("B")?),
+            MyNodeTemplate::VectorTimesScalar => {
+                vector(input_vector("vector")? * input_scalar("scalar")?)
+            }
+            MyNodeTemplate::AddVector => vector(input_vector("v1")? + input_vector("v2")?),
+            MyNodeTemplate::SubtractVector => vector(input_vector("v1")? - input_vector("v2")?),
+            MyNodeTemplate::MakeVector => {
+                vector(egui::vec2(input_scalar("x")?, input_scalar("y")?))
+            }
+            MyNodeTemplate::MakeScalar => scalar(input_scalar("value")?),
+        })
     }
 }
 
-fn populate_output(
-    graph: &MyGraph,
-    outputs_cache: &mut OutputsCache,
-    node_id: NodeId,
-    param_name: &str,
-    value: MyValueType,
-) -> anyhow::Result<MyValueType> {
-    let output_id = graph[node_id].get_output(param_name)?;
-    outputs_cache.insert(output_id, value);
-    Ok(value)
-}
-
-// Evaluates the input value of
-fn evaluate_input(
+/// Evaluates this node and all its dependencies, and returns the value of its
+/// output. All the nodes in this example have a single output named "out".
+/// Nodes already evaluated in the `outputs_cache` are not evaluated again.
+pub fn evaluate_node(
     graph: &MyGraph,
     node_id: NodeId,
-    param_name: &str,
-    outputs_cache: &mut OutputsCache,
+    outputs_cache: &mut OutputsCache<MyValueType>,
 ) -> anyhow::Result<MyValueType> {
-    let input_id = graph[node_id].get_input(param_name)?;
-
-    // The output of another node is connected.
-    if let Some(other_output_id) = graph.connection(input_id) {
-        // The value was already computed due to the evaluation of some other
-        // node. We simply return value from the cache.
-        if let Some(other_value) = outputs_cache.get(&other_output_id) {
-            Ok(*other_value)
-        }
-        // This is the first time encountering this node, so we need to
-        // recursively evaluate it.
-        else {
-            // Calling this will populate the cache
-            evaluate_node(graph, graph[other_output_id].node, outputs_cache)?;
-
-            // Now that we know the value is cached, return it
-            Ok(*outputs_cache
-                .get(&other_output_id)
-                .expect("Cache should be populated"))
-        }
-    }
-    // No existing connection, take the inline value instead.
-    else {
-        Ok(graph[input_id].value)
-    }
+    let output = graph[node_id].get_output("out")?;
+    Ok(graph.evaluate_output(output, outputs_cache, &mut ())?)
 }